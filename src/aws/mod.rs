@@ -0,0 +1,92 @@
+use crate::error::AthenaCliError;
+use anyhow::{Context, Result};
+use aws_smithy_http_client::tls::{self, rustls_provider::CryptoMode};
+use aws_smithy_http_client::Builder;
+use std::fs;
+
+pub mod profile;
+pub mod s3;
+
+/// Builds and returns an AWS SDK configuration based on the following priority:
+/// 1. Specified AWS profile (if provided)
+/// 2. AWS environment variables (if available)
+/// 3. Interactive SSO login (if user confirms)
+///
+/// This function can be reused to create any AWS service client.
+///
+/// Profile and region are resolved by [`crate::context::Context`] and passed in.
+/// The SDK itself reads the credential variables, `AWS_ENDPOINT_URL` (and the
+/// per-service `AWS_ENDPOINT_URL_ATHENA`, `AWS_ENDPOINT_URL_S3`, ...),
+/// `AWS_MAX_ATTEMPTS` and `AWS_RETRY_MODE`. The SDK doesn't support
+/// `AWS_CA_BUNDLE`, so `ca_bundle` adds its certificates to the trusted roots here.
+///
+/// A malformed region or a profile missing from the AWS config files is
+/// rejected before any request is made.
+pub async fn build_aws_config(
+    profile: Option<String>,
+    region: String,
+    ca_bundle: Option<String>,
+) -> Result<aws_config::SdkConfig> {
+    profile::validate_region(&region)?;
+    if let Some(profile_name) = &profile {
+        profile::validate_profile(profile_name)?;
+    }
+
+    let mut builder = aws_config::defaults(aws_config::BehaviorVersion::latest());
+
+    if let Some(profile_name) = profile {
+        builder = builder.profile_name(profile_name);
+    }
+
+    builder = builder.region(aws_config::Region::new(region));
+
+    if let Some(path) = ca_bundle {
+        let pem = fs::read(&path).context(format!("Failed to read CA bundle: {}", path))?;
+        let trust_store = tls::TrustStore::default().with_pem_certificate(pem);
+        let tls_context = tls::TlsContext::builder()
+            .with_trust_store(trust_store)
+            .build()
+            .context(format!("Invalid CA bundle: {}", path))?;
+
+        builder = builder.http_client(
+            Builder::new()
+                .tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc))
+                .tls_context(tls_context)
+                .build_https(),
+        );
+    }
+
+    Ok(builder.load().await)
+}
+
+/// Helper function to handle common AWS authentication errors with helpful messages.
+///
+/// Errors categorized as [`AthenaCliError::Auth`] get the hint; errors from calls
+/// that aren't categorized fall back to looking for auth-related words in the message.
+pub fn handle_aws_auth_error(err: anyhow::Error, profile: Option<String>) -> anyhow::Error {
+    let is_auth_error = match AthenaCliError::find(&err) {
+        Some(category) => matches!(category, AthenaCliError::Auth(_)),
+        None => {
+            let err_string = format!("{:?}", err);
+            err_string.contains("ForbiddenException")
+                || err_string.contains("AccessDenied")
+                || err_string.contains("ExpiredToken")
+                || err_string.contains("credentials")
+                || err_string.contains("auth")
+        }
+    };
+
+    if !is_auth_error {
+        return err;
+    }
+
+    eprintln!("AWS Authentication Error: Your credentials may be expired or insufficient.");
+
+    if let Some(profile_name) = profile {
+        eprintln!("\nPlease run: aws sso login --profile {}", profile_name);
+    } else {
+        eprintln!("\nPlease set valid AWS credentials or configure a profile.");
+    }
+
+    AthenaCliError::Auth("Authentication failure".to_string()).into()
+}
@@ -0,0 +1,153 @@
+//! Early checks of the profile and region, so setup mistakes are reported up
+//! front instead of surfacing from the first SDK call.
+
+use crate::error::AthenaCliError;
+use crate::utils::suggest::closest_matches;
+use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Checks the region looks like `eu-west-1` or `us-gov-west-1`: a two-letter
+/// area, one or more name parts and a single-digit number
+pub fn validate_region(region: &str) -> Result<()> {
+    let parts: Vec<&str> = region.split('-').collect();
+    let valid = match parts.as_slice() {
+        [area, names @ .., number] if !names.is_empty() => {
+            area.len() == 2
+                && area.chars().all(|c| c.is_ascii_lowercase())
+                && names
+                    .iter()
+                    .all(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase()))
+                && number.len() == 1
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        _ => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AthenaCliError::Validation(format!(
+            "Invalid AWS region '{}': expected a region such as 'eu-west-1'",
+            region
+        ))
+        .into())
+    }
+}
+
+/// Checks a named profile is defined in the shared AWS config or credentials
+/// file. The `default` profile is left alone, since credentials may come from
+/// the environment without any files.
+pub fn validate_profile(profile: &str) -> Result<()> {
+    if profile == "default" {
+        return Ok(());
+    }
+
+    let profiles = known_profiles();
+    if profiles.iter().any(|known| known == profile) {
+        return Ok(());
+    }
+
+    let suggestions = closest_matches(profile, profiles.iter().map(String::as_str));
+    let hint = if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" Did you mean: {}?", suggestions.join(", "))
+    };
+    Err(AthenaCliError::Validation(format!(
+        "AWS profile '{}' not found in the AWS config or credentials file.{} \
+         Run 'aws configure list-profiles' to see the available profiles",
+        profile, hint
+    ))
+    .into())
+}
+
+/// Profile names from the shared config and credentials files, honoring
+/// `AWS_CONFIG_FILE` and `AWS_SHARED_CREDENTIALS_FILE`
+fn known_profiles() -> Vec<String> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let config_file = env::var("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| home.join(".aws/config"));
+    let credentials_file = env::var("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| home.join(".aws/credentials"));
+
+    let mut profiles = Vec::new();
+    if let Ok(contents) = fs::read_to_string(config_file) {
+        profiles.extend(profile_names(&contents, true));
+    }
+    if let Ok(contents) = fs::read_to_string(credentials_file) {
+        profiles.extend(profile_names(&contents, false));
+    }
+    profiles
+}
+
+/// Profile names defined by the `[section]` headers of a shared AWS file. In the
+/// config file profiles other than `default` are written `[profile name]`, and
+/// other sections (e.g. `[sso-session name]`) aren't profiles.
+fn profile_names(contents: &str, is_config_file: bool) -> Vec<String> {
+    contents
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+        .map(str::trim)
+        .filter_map(|section| {
+            if !is_config_file || section == "default" {
+                return Some(section.to_string());
+            }
+            section
+                .strip_prefix("profile")
+                .filter(|name| name.starts_with(char::is_whitespace))
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_region() {
+        for region in [
+            "eu-west-1",
+            "us-east-1",
+            "ap-southeast-2",
+            "us-gov-west-1",
+            "cn-north-1",
+        ] {
+            assert!(
+                validate_region(region).is_ok(),
+                "{} should be accepted",
+                region
+            );
+        }
+        for region in [
+            "us-east-99",
+            "useast1",
+            "us-east",
+            "US-EAST-1",
+            "u-east-1",
+            "us--1",
+            "",
+        ] {
+            assert!(
+                validate_region(region).is_err(),
+                "{:?} should be rejected",
+                region
+            );
+        }
+    }
+
+    #[test]
+    fn test_profile_names() {
+        let config = "[default]\nregion = eu-west-1\n\n[profile analytics]\nsso_session = corp\n\
+                      [sso-session corp]\n[profilex]\n";
+        assert_eq!(profile_names(config, true), vec!["default", "analytics"]);
+
+        let credentials = "[default]\naws_access_key_id = x\n[ci]\n";
+        assert_eq!(profile_names(credentials, false), vec!["default", "ci"]);
+    }
+}
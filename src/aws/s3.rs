@@ -0,0 +1,310 @@
+//! S3 URL handling shared by the result downloader and query output locations.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+/// Extracts the bucket and key from an S3 URL.
+///
+/// Accepts `s3://bucket/key`, virtual-hosted `https://bucket.s3.region.amazonaws.com/key`
+/// and path-style `https://s3.region.amazonaws.com/bucket/key` URLs. Keys in the
+/// `https://` forms are percent-decoded; `s3://` keys are used as written.
+pub fn parse_s3_url(s3_url: &str) -> Result<(String, String)> {
+    let url = Url::parse(s3_url).context(format!("Failed to parse S3 URL: {}", s3_url))?;
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("Invalid S3 URL: no host in {}", s3_url))?;
+
+    if let Some(stripped) = s3_url.strip_prefix("s3://") {
+        // s3://bucket-name/key format
+        let parts: Vec<&str> = stripped.splitn(2, '/').collect();
+
+        if parts.len() < 2 {
+            return Err(anyhow!("Invalid S3 URL format (s3://): {}", s3_url));
+        }
+
+        Ok((parts[0].to_string(), parts[1].to_string()))
+    } else if let Some(bucket_name) = virtual_hosted_bucket(host) {
+        // https://bucket-name.s3.region.amazonaws.com/key format
+        let object_key = url.path().strip_prefix('/').unwrap_or(url.path());
+
+        Ok((bucket_name.to_string(), decode_key(object_key)?))
+    } else {
+        // https://s3.region.amazonaws.com/bucket-name/key format
+        let path_segments = url
+            .path_segments()
+            .ok_or_else(|| anyhow!("Invalid S3 URL: no path in {}", s3_url))?
+            .collect::<Vec<_>>();
+
+        if path_segments[0].is_empty() {
+            return Err(anyhow!("Invalid S3 URL: empty path in {}", s3_url));
+        }
+
+        let bucket_name = path_segments[0];
+        let object_key = path_segments[1..].join("/");
+
+        Ok((bucket_name.to_string(), decode_key(&object_key)?))
+    }
+}
+
+/// Percent-decodes an object key taken from the path of an `https://` URL
+fn decode_key(key: &str) -> Result<String> {
+    percent_decode_str(key)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .context(format!("Invalid S3 URL: key is not valid UTF-8: {}", key))
+}
+
+/// Bucket name of a virtual-hosted style host such as `bucket.s3.region.amazonaws.com`.
+///
+/// Path-style hosts (`s3.amazonaws.com`, `s3.region.amazonaws.com` and the older
+/// `s3-region.amazonaws.com`) have no bucket in them and return `None`.
+fn virtual_hosted_bucket(host: &str) -> Option<&str> {
+    if !host.ends_with(".amazonaws.com") || host.starts_with("s3.") || host.starts_with("s3-") {
+        return None;
+    }
+
+    // Bucket names may contain dots, so split at the service label rather than the first dot
+    let end = host.find(".s3.").or_else(|| host.find(".s3-"))?;
+    Some(&host[..end]).filter(|bucket| !bucket.is_empty())
+}
+
+/// Checks that a query output location is an `s3://bucket/prefix` URL with a
+/// valid bucket name, so a typo is reported before a query is started
+pub fn validate_output_location(location: &str) -> Result<()> {
+    if !location.starts_with("s3://") {
+        return Err(anyhow!(
+            "Invalid output location '{}': expected an s3://bucket/prefix URL",
+            location
+        ));
+    }
+
+    // A bare bucket is a valid location, but the parser expects a key part
+    let url = if location["s3://".len()..].contains('/') {
+        location.to_string()
+    } else {
+        format!("{}/", location)
+    };
+    let (bucket, _) =
+        parse_s3_url(&url).with_context(|| format!("Invalid output location '{}'", location))?;
+
+    if !is_valid_bucket_name(&bucket) {
+        return Err(anyhow!(
+            "Invalid output location '{}': '{}' is not a valid S3 bucket name",
+            location,
+            bucket
+        ));
+    }
+
+    Ok(())
+}
+
+/// Values for the placeholders an output location may contain
+pub struct OutputLocationValues<'a> {
+    pub date: NaiveDate,
+    pub workgroup: &'a str,
+    pub database: &'a str,
+    pub user: Option<&'a str>,
+}
+
+/// Placeholders understood by [`expand_output_location`]
+const OUTPUT_LOCATION_PLACEHOLDERS: &[&str] = &["date", "workgroup", "database", "user"];
+
+/// Fills in `{date}` (YYYY-MM-DD), `{workgroup}`, `{database}` and `{user}` in an
+/// output location such as `s3://bucket/results/{date}/{workgroup}/`
+pub fn expand_output_location(template: &str, values: &OutputLocationValues) -> Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            anyhow!(
+                "Invalid output location '{}': unclosed '{{' placeholder",
+                template
+            )
+        })? + start;
+
+        let value = match &rest[start + 1..end] {
+            "date" => values.date.format("%Y-%m-%d").to_string(),
+            "workgroup" => values.workgroup.to_string(),
+            "database" => values.database.to_string(),
+            "user" => values.user.map(str::to_string).ok_or_else(|| {
+                anyhow!("Can't fill in {{user}} in the output location: USER is not set")
+            })?,
+            name => {
+                return Err(anyhow!(
+                    "Unknown placeholder {{{}}} in output location '{}' (available: {})",
+                    name,
+                    template,
+                    OUTPUT_LOCATION_PLACEHOLDERS
+                        .iter()
+                        .map(|name| format!("{{{}}}", name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        };
+        expanded.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// S3 bucket naming rules: 3-63 lowercase letters, digits, dots and hyphens,
+/// starting and ending with a letter or digit
+fn is_valid_bucket_name(bucket: &str) -> bool {
+    let is_edge = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+
+    (3..=63).contains(&bucket.len())
+        && bucket.chars().all(|c| is_edge(c) || c == '.' || c == '-')
+        && bucket.starts_with(is_edge)
+        && bucket.ends_with(is_edge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(url: &str) -> (String, String) {
+        parse_s3_url(url).unwrap()
+    }
+
+    fn pair(bucket: &str, key: &str) -> (String, String) {
+        (bucket.to_string(), key.to_string())
+    }
+
+    #[test]
+    fn test_parse_s3_url() {
+        assert_eq!(
+            parsed("s3://my-bucket/results/2024/abc.csv"),
+            pair("my-bucket", "results/2024/abc.csv")
+        );
+        assert_eq!(
+            parsed("https://my-bucket.s3.eu-west-1.amazonaws.com/results/2024/abc.csv"),
+            pair("my-bucket", "results/2024/abc.csv")
+        );
+        assert_eq!(
+            parsed("https://s3.eu-west-1.amazonaws.com/my-bucket/results/2024/abc.csv"),
+            pair("my-bucket", "results/2024/abc.csv")
+        );
+    }
+
+    #[test]
+    fn test_parse_s3_url_host_styles() {
+        // Dotted bucket names and legacy hosts
+        assert_eq!(
+            parsed("https://my.dotted.bucket.s3.us-east-1.amazonaws.com/a/b.csv"),
+            pair("my.dotted.bucket", "a/b.csv")
+        );
+        assert_eq!(
+            parsed("https://my-bucket.s3.amazonaws.com/a/b.csv"),
+            pair("my-bucket", "a/b.csv")
+        );
+        assert_eq!(
+            parsed("https://my-bucket.s3-us-west-2.amazonaws.com/a/b.csv"),
+            pair("my-bucket", "a/b.csv")
+        );
+        assert_eq!(
+            parsed("https://s3.amazonaws.com/my-bucket/a/b.csv"),
+            pair("my-bucket", "a/b.csv")
+        );
+        assert_eq!(
+            parsed("https://s3-us-west-2.amazonaws.com/my-bucket/a/b.csv"),
+            pair("my-bucket", "a/b.csv")
+        );
+    }
+
+    #[test]
+    fn test_parse_s3_url_keys() {
+        // s3:// keys are taken verbatim, including characters that look URL-encoded
+        assert_eq!(
+            parsed("s3://my-bucket/dt=2024-01-01/part%2000.csv"),
+            pair("my-bucket", "dt=2024-01-01/part%2000.csv")
+        );
+
+        // https:// keys are percent-decoded
+        assert_eq!(
+            parsed("https://my-bucket.s3.eu-west-1.amazonaws.com/dir/sub%20dir/a%2Bb.csv"),
+            pair("my-bucket", "dir/sub dir/a+b.csv")
+        );
+        assert_eq!(
+            parsed("https://s3.eu-west-1.amazonaws.com/my-bucket/dir/sub%20dir/a.csv"),
+            pair("my-bucket", "dir/sub dir/a.csv")
+        );
+        assert!(parse_s3_url("https://my-bucket.s3.amazonaws.com/%FF.csv").is_err());
+
+        // Bucket only
+        assert_eq!(parsed("s3://my-bucket/"), pair("my-bucket", ""));
+        assert_eq!(
+            parsed("https://s3.eu-west-1.amazonaws.com/my-bucket"),
+            pair("my-bucket", "")
+        );
+
+        assert!(parse_s3_url("s3://my-bucket").is_err());
+        assert!(parse_s3_url("https://s3.eu-west-1.amazonaws.com/").is_err());
+        assert!(parse_s3_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_validate_output_location() {
+        assert!(validate_output_location("s3://my-results/athena/").is_ok());
+        assert!(validate_output_location("s3://my-results").is_ok());
+        assert!(validate_output_location("s3://my.results-2024/a/b").is_ok());
+
+        for location in [
+            "",
+            "my-results/athena/",
+            "https://my-results.s3.amazonaws.com/athena/",
+            "s3://",
+            "s3:///athena/",
+            "s3://My_Results/athena/",
+            "s3://ab/",
+            "s3://-results/",
+        ] {
+            assert!(
+                validate_output_location(location).is_err(),
+                "{:?} should be rejected",
+                location
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_output_location() {
+        let values = OutputLocationValues {
+            date: NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(),
+            workgroup: "analytics",
+            database: "sales",
+            user: Some("jane"),
+        };
+
+        assert_eq!(
+            expand_output_location("s3://results/{date}/{workgroup}/", &values).unwrap(),
+            "s3://results/2024-03-09/analytics/"
+        );
+        assert_eq!(
+            expand_output_location("s3://results/{user}/{database}/{date}", &values).unwrap(),
+            "s3://results/jane/sales/2024-03-09"
+        );
+        assert_eq!(
+            expand_output_location("s3://results/plain/", &values).unwrap(),
+            "s3://results/plain/"
+        );
+
+        assert!(expand_output_location("s3://results/{day}/", &values).is_err());
+        assert!(expand_output_location("s3://results/{date/", &values).is_err());
+        assert!(expand_output_location(
+            "s3://results/{user}/",
+            &OutputLocationValues {
+                user: None,
+                ..values
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Local cache of downloaded query results, keyed by query execution ID.
+//!
+//! Each cached object is stored as `<cache dir>/<query id>/<file name>` next to a
+//! `<file name>.json` entry recording the S3 ETag and size it was downloaded with.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// S3 object details a cached file was downloaded with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub etag: String,
+    pub size: i64,
+}
+
+/// A file found in the cache, along with the entry it was stored with
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub path: PathBuf,
+    pub entry: CacheEntry,
+}
+
+impl CachedFile {
+    /// Whether the cached copy still matches the object currently in S3
+    pub fn is_fresh(&self, etag: Option<&str>, size: Option<i64>) -> bool {
+        etag == Some(self.entry.etag.as_str()) && size == Some(self.entry.size)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    root: PathBuf,
+}
+
+impl ResultCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Opens the cache under the config directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::new(crate::config::get_cache_dir()?))
+    }
+
+    /// Looks up a cached copy of `filename` downloaded for `query_id`
+    pub fn lookup(&self, query_id: &str, filename: &str) -> Option<CachedFile> {
+        let path = self.file_path(query_id, filename).ok()?;
+        let entry = fs::read_to_string(entry_path(&path)).ok()?;
+        let entry = serde_json::from_str(&entry).ok()?;
+
+        path.is_file().then_some(CachedFile { path, entry })
+    }
+
+    /// Starts writing a new copy of `filename` for `query_id`. The copy only
+    /// becomes visible to [`lookup`](Self::lookup) once [`CacheWriter::commit`] succeeds.
+    pub fn writer(&self, query_id: &str, filename: &str, entry: CacheEntry) -> Result<CacheWriter> {
+        let path = self.file_path(query_id, filename)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context(format!(
+                "Failed to create cache directory: {}",
+                parent.display()
+            ))?;
+        }
+
+        // Drop any stale entry so a half-written copy is never treated as valid
+        let _ = fs::remove_file(entry_path(&path));
+
+        let partial_path = path.with_file_name(format!("{}.partial", filename));
+        let file = File::create(&partial_path).context(format!(
+            "Failed to create cache file: {}",
+            partial_path.display()
+        ))?;
+
+        Ok(CacheWriter {
+            file: BufWriter::new(file),
+            partial_path,
+            path,
+            entry,
+        })
+    }
+
+    /// Removes every cached result, returning how many queries were cleared
+    pub fn clear(&self) -> Result<usize> {
+        if !self.root.exists() {
+            return Ok(0);
+        }
+
+        let queries = fs::read_dir(&self.root)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .count();
+        fs::remove_dir_all(&self.root).context(format!(
+            "Failed to remove cache directory: {}",
+            self.root.display()
+        ))?;
+
+        Ok(queries)
+    }
+
+    fn file_path(&self, query_id: &str, filename: &str) -> Result<PathBuf> {
+        // Query IDs come from the command line, so keep them from escaping the cache
+        if query_id.is_empty()
+            || !query_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(anyhow!("Cannot cache results for query ID: {}", query_id));
+        }
+
+        Ok(self.root.join(query_id).join(filename))
+    }
+}
+
+/// Writes a downloaded object into the cache
+pub struct CacheWriter {
+    file: BufWriter<File>,
+    partial_path: PathBuf,
+    path: PathBuf,
+    entry: CacheEntry,
+}
+
+impl CacheWriter {
+    pub fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.file.write_all(buf)
+    }
+
+    /// Finishes the copy and records its entry so later lookups can use it
+    pub fn commit(mut self) -> Result<()> {
+        self.file.flush()?;
+        fs::rename(&self.partial_path, &self.path)?;
+        fs::write(entry_path(&self.path), serde_json::to_string(&self.entry)?)?;
+        Ok(())
+    }
+}
+
+fn entry_path(path: &Path) -> PathBuf {
+    let mut entry = path.as_os_str().to_owned();
+    entry.push(".json");
+    PathBuf::from(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY_ID: &str = "8f3c2b1a-1234-4d5e-9abc-0123456789ab";
+
+    fn entry() -> CacheEntry {
+        CacheEntry {
+            etag: "\"abc123\"".to_string(),
+            size: 6,
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(dir.path());
+
+        assert!(cache.lookup(QUERY_ID, "results.csv").is_none());
+
+        // Uncommitted copies are not visible
+        let mut writer = cache.writer(QUERY_ID, "results.csv", entry()).unwrap();
+        writer.write_all(b"id\n1\n").unwrap();
+        assert!(cache.lookup(QUERY_ID, "results.csv").is_none());
+        writer.commit().unwrap();
+
+        let cached = cache.lookup(QUERY_ID, "results.csv").unwrap();
+        assert_eq!(fs::read(&cached.path).unwrap(), b"id\n1\n");
+        assert!(cached.is_fresh(Some("\"abc123\""), Some(6)));
+        assert!(!cached.is_fresh(Some("\"def456\""), Some(6)));
+        assert!(!cached.is_fresh(Some("\"abc123\""), Some(7)));
+        assert!(!cached.is_fresh(None, Some(6)));
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.lookup(QUERY_ID, "results.csv").is_none());
+        assert_eq!(cache.clear().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_cache_rejects_unsafe_query_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResultCache::new(dir.path().join("cache"));
+
+        assert!(cache.writer("../escape", "results.csv", entry()).is_err());
+        assert!(cache.writer("", "results.csv", entry()).is_err());
+        assert!(cache.lookup("../escape", "results.csv").is_none());
+    }
+}
@@ -1,4 +1,11 @@
-use clap::{Args, Parser, Subcommand};
+use crate::commands::export::parse_delimiter;
+use crate::config::{OutputFormat, TableStyle};
+use crate::utils::filter::parse_column_filter;
+use crate::utils::labels::parse_label;
+use crate::utils::params::parse_param;
+use crate::utils::row_assertion::{parse_row_assertion, RowAssertion};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use humantime::parse_duration;
 use std::time::Duration;
 
@@ -36,6 +43,182 @@ pub struct DisplayArgs {
     /// Suppress detailed output
     #[arg(short, long, global = true)]
     pub quiet: bool,
+
+    /// Increase diagnostic output (-v for details, -vv for debug)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// When to use colored output (honors NO_COLOR in auto mode)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Cut off table values longer than this many characters with "..."
+    /// (overrides app.max_column_width, 0 for no limit)
+    #[arg(long, global = true, value_name = "CHARS")]
+    pub max_col_width: Option<usize>,
+
+    /// Never pipe long tables through a pager (also disabled by PAGER=cat or an empty PAGER)
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Border style of tables (overrides app.table_style)
+    #[arg(long, global = true, value_enum)]
+    pub table_style: Option<TableStyle>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorChoice {
+    /// Color only when writing to a terminal
+    #[default]
+    Auto,
+    /// Always emit color codes
+    Always,
+    /// Never emit color codes
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum FollowFormat {
+    /// Tab-separated rows under a header line
+    #[default]
+    Table,
+    /// One JSON object per row
+    Ndjson,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnloadFormat {
+    #[default]
+    Parquet,
+    Orc,
+    Avro,
+    Json,
+    /// Comma-separated text files
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ListFormat {
+    /// Human-readable table
+    #[default]
+    Table,
+    /// JSON array, for feeding into other tools
+    Json,
+}
+
+impl From<ListFormat> for OutputFormat {
+    fn from(format: ListFormat) -> Self {
+        match format {
+            ListFormat::Table => Self::Table,
+            ListFormat::Json => Self::Json,
+        }
+    }
+}
+
+/// State of a query execution, as used by history's --status filter
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "UPPER")]
+pub enum QueryState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl QueryState {
+    /// The state as Athena reports it, e.g. "SUCCEEDED"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "QUEUED",
+            Self::Running => "RUNNING",
+            Self::Succeeded => "SUCCEEDED",
+            Self::Failed => "FAILED",
+            Self::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+/// State of a workgroup, as used by workgroup's --state filter
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "UPPER")]
+pub enum WorkgroupState {
+    Enabled,
+    Disabled,
+}
+
+impl WorkgroupState {
+    /// The state as Athena reports it, e.g. "ENABLED"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enabled => "ENABLED",
+            Self::Disabled => "DISABLED",
+        }
+    }
+}
+
+/// Order of the workgroup listing
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum WorkgroupSortBy {
+    /// Alphabetically by name
+    Name,
+    /// Oldest first, by creation time
+    Created,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColumnsOrder {
+    /// Regular columns and partition keys in separate tables
+    #[default]
+    Split,
+    /// One table of all columns, regular ones first and then the partition keys,
+    /// with a Partition column, as SHOW CREATE TABLE lists them
+    Merged,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum DescribeFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// JSON object with the columns, partition keys, table type and parameters,
+    /// for code generators and data catalogs
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExplainFormat {
+    #[default]
+    Text,
+    Json,
+    Graphviz,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ResultFormat {
+    Csv,
+    Json,
+    Parquet,
+    /// Arrow IPC file, readable by pyarrow, pandas, DuckDB and Polars with types preserved
+    #[value(alias = "ipc")]
+    Arrow,
+    /// One JSON object per row (JSON Lines)
+    #[value(alias = "jsonl")]
+    Ndjson,
+}
+
+/// Which CSV fields are put in quotes
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CsvQuoteStyle {
+    /// Only fields containing the delimiter, quotes or line breaks
+    #[default]
+    Necessary,
+    /// Every field
+    Always,
+    /// Every field that isn't a number
+    NonNumeric,
+    /// No field, even when that makes the file ambiguous
+    Never,
 }
 
 // Shared arguments for commands that support file output
@@ -62,7 +245,10 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Execute a query
-    Query(QueryArgs),
+    Query(Box<QueryArgs>),
+
+    /// Show the execution plan for a query without running it
+    Explain(ExplainArgs),
 
     /// Database operations
     Database {
@@ -88,9 +274,59 @@ pub enum Commands {
     /// Inspect details of a specific query
     Inspect(InspectArgs),
 
+    /// Run a previous query again, by its execution ID
+    Rerun(RerunArgs),
+
+    /// Cancel a running query
+    Cancel(CancelArgs),
+
+    /// Delete old query result files from S3 (a dry run unless --confirm is given)
+    Cleanup(CleanupArgs),
+
+    /// Summarize a workgroup's recent CloudWatch query metrics
+    Metrics(MetricsArgs),
+
+    /// Start an interactive session for running several queries
+    Repl(ReplArgs),
+
+    /// Create a prepared statement in the current workgroup
+    Prepare(PrepareArgs),
+
+    /// Manage prepared statements in the current workgroup
+    #[command(name = "prepared-statement")]
+    PreparedStatement {
+        #[command(subcommand)]
+        command: PreparedStatementCommands,
+    },
+
+    /// Browse the local library of .sql files run with 'query --name'
+    Library {
+        #[command(subcommand)]
+        command: LibraryCommands,
+    },
+
+    /// Manage saved queries in the current workgroup
+    #[command(name = "named-query")]
+    NamedQuery {
+        #[command(subcommand)]
+        command: NamedQueryCommands,
+    },
+
     /// Download query results (shortcut for 'inspect -o')
     #[command(alias = "dl")] // Optional: add even shorter alias
     Download(DownloadArgs),
+
+    /// Show the resolved AWS identity and Athena settings
+    Whoami,
+
+    /// Generate shell completion scripts
+    Completion(CompletionArgs),
+
+    /// Manage local CLI state
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -114,6 +350,72 @@ pub enum WorkgroupCommands {
     List(WorkgroupArgs),
 }
 
+#[derive(Subcommand)]
+pub enum NamedQueryCommands {
+    /// List saved queries in the current workgroup and database
+    List,
+
+    /// Show a saved query's SQL
+    Show(NamedQueryIdArgs),
+
+    /// Save a query in the current workgroup
+    Create(CreateNamedQueryArgs),
+
+    /// Delete a saved query
+    Delete(NamedQueryIdArgs),
+}
+
+#[derive(Subcommand)]
+pub enum LibraryCommands {
+    /// List the library's queries with the first comment line of each
+    List(LibraryArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct LibraryArgs {
+    /// Query library directory (overrides app.query_library_dir)
+    #[arg(long, value_name = "DIR")]
+    pub sql_file_dir: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum PreparedStatementCommands {
+    /// List prepared statements in the current workgroup
+    List,
+
+    /// Delete a prepared statement
+    Delete(PreparedStatementArgs),
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Create the config file by answering a few questions
+    Init(ConfigInitArgs),
+
+    /// Show each effective setting and whether it came from a flag, an
+    /// environment variable, the config file or the built-in default
+    Resolve,
+
+    /// Manage locally cached query results
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+}
+
+#[derive(Args, Clone)]
+pub struct ConfigInitArgs {
+    /// Replace an existing config file
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Remove all cached query results
+    Clear,
+}
+
 #[derive(Args, Clone)]
 pub struct QueryArgs {
     #[command(flatten)]
@@ -122,7 +424,76 @@ pub struct QueryArgs {
     /// SQL query to execute (can be a full SQL statement)
     ///
     /// Example: "SELECT * FROM my_database.my_table LIMIT 10"
-    pub query: String,
+    #[arg(required_unless_present_any = ["named", "prepared", "batch", "name"])]
+    pub query: Option<String>,
+
+    /// Run the saved query with this name from the current workgroup instead
+    #[arg(long, conflicts_with = "query")]
+    pub named: Option<String>,
+
+    /// Run this prepared statement from the current workgroup instead (see 'prepare')
+    #[arg(long, conflicts_with_all = ["query", "named"])]
+    pub prepared: Option<String>,
+
+    /// Run NAME.sql from the local query library instead (see 'library list').
+    /// --param values fill in its placeholders
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["query", "named", "prepared", "batch"])]
+    pub name: Option<String>,
+
+    /// Query library directory to look up --name in (overrides app.query_library_dir)
+    #[arg(long, value_name = "DIR", requires = "name")]
+    pub sql_file_dir: Option<String>,
+
+    /// Run every ';'-separated statement in this file as its own execution and
+    /// report each one's ID, status and data scanned instead of fetching results
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "query", "named", "prepared", "repeat", "follow", "unload_to", "output", "tee",
+            "format", "select", "where_contains"
+        ]
+    )]
+    pub batch: Option<String>,
+
+    /// How many --batch statements to run at the same time [default: 1]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "batch",
+        conflicts_with_all = ["query", "named", "prepared"]
+    )]
+    pub parallel: Option<u32>,
+
+    /// Value for the prepared statement's next '?' parameter, as a SQL literal (repeatable)
+    ///
+    /// Example: --prepared daily --use "'2024-01-01'" --use 5
+    #[arg(
+        long = "use",
+        value_name = "VALUE",
+        requires = "prepared",
+        conflicts_with_all = ["query", "named"]
+    )]
+    pub using: Vec<String>,
+
+    /// Substitute a value for ':key' or '${key}' placeholders in the query (repeatable).
+    /// Single quotes in the value are escaped
+    ///
+    /// Example: --param date=2024-01-01 "SELECT * FROM t WHERE dt = ':date'"
+    #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+
+    /// Fail if the query contains placeholders without a --param value
+    #[arg(long)]
+    pub strict_params: bool,
+
+    /// Attach a label to the query as a leading '-- tag:key=value' comment, so it
+    /// shows in the query text in history (repeatable, see 'history --grep')
+    ///
+    /// Example: --label team=analytics --label env=prod
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+    pub labels: Vec<(String, String)>,
 
     /// Query reuse time (e.g., "10m", "2h", "1h30m") - specifies how long cached results should be reused
     ///
@@ -130,13 +501,243 @@ pub struct QueryArgs {
     /// which can save on costs and improve performance. Set to 0 to disable result reuse.
     #[arg(short = 'r', long, value_parser = parse_duration, default_value = "60m")]
     pub reuse_time: Duration,
+
+    /// Always run the query fresh, the same as --reuse-time 0
+    #[arg(long, conflicts_with = "reuse_time")]
+    pub no_reuse: bool,
+
+    /// Run the query this many times in a row with result reuse disabled, then report
+    /// the min/median/max engine time and data scanned instead of the results
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["follow", "unload_to", "output", "tee", "format", "select", "where_contains"]
+    )]
+    pub repeat: Option<u32>,
+
+    /// Print rows page by page as soon as the query succeeds instead of waiting for
+    /// the full result (use --follow=ndjson for one JSON object per row)
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "table"
+    )]
+    pub follow: Option<FollowFormat>,
+
+    /// Fetch and show at most this many rows (overrides app.max_rows, 0 for no limit).
+    /// Results saved with --output are never capped
+    #[arg(long)]
+    pub max_rows: Option<usize>,
+
+    /// Show only the first N rows of the results. Unlike a LIMIT, the query still
+    /// returns every row, and saved files keep all of them
+    #[arg(long, value_name = "N", conflicts_with = "follow")]
+    pub head: Option<usize>,
+
+    /// Print each row as one "column: value" line per column, like MySQL's \G.
+    /// Used automatically for a single row too wide for the terminal
+    #[arg(long, conflicts_with = "follow")]
+    pub vertical: bool,
+
+    /// Don't add the configured app.auto_limit to a query without a LIMIT
+    #[arg(long)]
+    pub no_limit: bool,
+
+    /// Check that every table the query reads exists before running it, suggesting
+    /// close names for any that don't (costs one API call per table)
+    #[arg(long)]
+    pub check_tables: bool,
+
+    /// Send the query to Athena without the client-side SQL syntax check, for valid
+    /// Athena syntax the checker rejects. app.auto_limit isn't applied either
+    #[arg(long, conflicts_with = "check_tables")]
+    pub no_validate: bool,
+
+    /// Keep only result rows whose column contains this text, ignoring case
+    /// (repeatable, rows must match all of them)
+    ///
+    /// Example: --where-contains status=fail --where-contains region=eu
+    #[arg(
+        long,
+        value_name = "COLUMN=TEXT",
+        value_parser = parse_column_filter,
+        conflicts_with_all = ["follow", "unload_to"]
+    )]
+    pub where_contains: Vec<(String, String)>,
+
+    /// Show or save only these result columns, in the order given (comma-separated)
+    ///
+    /// Example: --select id,name
+    #[arg(
+        long,
+        visible_alias = "output-columns",
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        conflicts_with_all = ["follow", "unload_to"]
+    )]
+    pub select: Vec<String>,
+
+    /// Run the query as an UNLOAD that writes its results as files under this
+    /// (empty) S3 prefix, instead of fetching them
+    #[arg(long, conflicts_with_all = ["prepared", "follow", "output", "format"])]
+    pub unload_to: Option<String>,
+
+    /// File format written by --unload-to
+    #[arg(long, value_enum, default_value_t = UnloadFormat::Parquet)]
+    pub unload_format: UnloadFormat,
+
+    /// Report whether Athena reused a previous execution's results, and which one
+    #[arg(long)]
+    pub explain_reuse: bool,
+
+    /// After the query finishes, print its full statistics (execution times,
+    /// data scanned and row count) to stderr, as `inspect` shows them
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Print only the number of rows the query returns, running it wrapped in
+    /// SELECT count(*) so no rows are fetched. The query must be a SELECT
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "prepared", "batch", "repeat", "follow", "head", "vertical", "no_validate",
+            "unload_to", "output", "tee", "format", "select", "where_contains"
+        ]
+    )]
+    pub count_only: bool,
+
+    /// Exit with code 7 when the query returns no rows, e.g. to fail a pipeline on
+    /// missing data. The (empty) results are still printed or saved. For CREATE
+    /// TABLE AS and UNLOAD statements, the rows written are checked
+    #[arg(long, conflicts_with_all = ["batch", "repeat", "unload_to"])]
+    pub fail_empty: bool,
+
+    /// Exit with code 7 unless the number of rows returned meets this comparison:
+    /// <, <=, ==, >= or > and a count. Every row is fetched to count them, so
+    /// --max-rows doesn't apply (use --head to show fewer). For CREATE TABLE AS
+    /// and UNLOAD statements, the rows written are checked
+    ///
+    /// Example: --assert-rows ">100"
+    #[arg(
+        long,
+        value_name = "EXPR",
+        value_parser = parse_row_assertion,
+        conflicts_with_all = ["batch", "repeat", "unload_to"]
+    )]
+    pub assert_rows: Option<RowAssertion>,
+
+    /// Show the data scanned against the workgroup's per-query scan limit, if it
+    /// has one. A warning is shown without this once a query comes close to it
+    #[arg(long)]
+    pub show_cutoff: bool,
+
+    /// Save the results to this file, or to "<query-id>.<format>" inside this directory,
+    /// instead of printing them
+    #[arg(short, long, group = "destination", conflicts_with = "follow")]
+    pub output: Option<String>,
+
+    /// Save the results like --output, and print them as well
+    #[arg(long, value_name = "PATH", group = "destination", conflicts_with_all = ["follow", "unload_to"])]
+    pub tee: Option<String>,
+
+    /// Format of the saved results (defaults to the --output or --tee file extension, then
+    /// app.query.format or app.default_format, then csv).
+    /// json without a file prints the rows as a JSON array, and with --output prints the
+    /// execution ID, S3 output location and saved file as a JSON object. ndjson without a
+    /// file streams the rows to stdout like --follow=ndjson
+    #[arg(
+        short,
+        long,
+        value_enum,
+        requires_ifs = [("csv", "destination"), ("parquet", "destination"), ("arrow", "destination")]
+    )]
+    pub format: Option<ResultFormat>,
+
+    /// Field delimiter of saved CSV files, a single character ("\t" for tabs).
+    /// Spreadsheets in some locales expect ';'
+    #[arg(long, value_parser = parse_delimiter, default_value = ",")]
+    pub delimiter: u8,
+
+    /// Start saved CSV files with a UTF-8 byte order mark, so Excel reads them as UTF-8
+    #[arg(long)]
+    pub bom: bool,
+
+    /// Which fields of saved CSV files are quoted
+    #[arg(long, value_enum, default_value_t = CsvQuoteStyle::Necessary)]
+    pub quote_style: CsvQuoteStyle,
+}
+
+impl QueryArgs {
+    /// Arguments that run `sql` with every other option at its default, for
+    /// commands that run queries on the user's behalf
+    pub fn for_sql(sql: String, reuse_time: Duration) -> Self {
+        Self {
+            aws: AwsArgs::default(),
+            query: Some(sql),
+            named: None,
+            prepared: None,
+            name: None,
+            sql_file_dir: None,
+            using: Vec::new(),
+            params: Vec::new(),
+            strict_params: false,
+            labels: Vec::new(),
+            reuse_time,
+            no_reuse: false,
+            repeat: None,
+            batch: None,
+            parallel: None,
+            follow: None,
+            max_rows: None,
+            no_limit: false,
+            check_tables: false,
+            no_validate: false,
+            where_contains: Vec::new(),
+            select: Vec::new(),
+            unload_to: None,
+            unload_format: Default::default(),
+            explain_reuse: false,
+            stats: false,
+            count_only: false,
+            fail_empty: false,
+            assert_rows: None,
+            show_cutoff: false,
+            head: None,
+            vertical: false,
+            delimiter: b',',
+            bom: false,
+            quote_style: Default::default(),
+            output: None,
+            tee: None,
+            format: None,
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct ExplainArgs {
+    /// SQL query to explain
+    ///
+    /// Example: "SELECT * FROM my_database.my_table WHERE dt = '2024-01-01'"
+    pub query: String,
+
+    /// Format of the plan
+    #[arg(short, long, value_enum, default_value_t = ExplainFormat::Text)]
+    pub format: ExplainFormat,
 }
 
 #[derive(Args, Clone)]
 pub struct DatabaseArgs {
-    // Empty - will use global catalog from AwsArgs
+    // Uses the global catalog from AwsArgs
     #[command(flatten)]
     pub aws: AwsArgs,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    pub format: ListFormat,
 }
 
 #[derive(Args, Clone)]
@@ -145,30 +746,74 @@ pub struct TableArgs {
     #[arg(short = 'n', long)]
     pub db: Option<String>,
 
-    /// Filter table names by pattern (e.g. "pp_*" for tables starting with pp_)
+    /// Filter table names by pattern (e.g. "pp_*" for tables starting with pp_, "?" matches one character)
     #[arg(short, long)]
     pub filter: Option<String>,
 
+    /// Match the filter pattern case-sensitively
+    #[arg(long)]
+    pub case_sensitive: bool,
+
     /// Maximum number of tables to list
     #[arg(short, long, default_value = "50")]
     pub limit: i32,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    pub format: ListFormat,
 }
 
 #[derive(Args, Clone)]
 pub struct DescribeTableArgs {
-    /// Table identifier (can be 'database.table' or just 'table')
+    /// Table identifier: 'table', 'database.table' or 'catalog.database.table'.
+    /// Quote parts containing dots with double quotes or backticks
     pub table: String,
 
     /// Database name (alternative to using 'database.table' format)
     #[arg(short = 'n', long)]
     pub db: Option<String>,
+
+    /// Also list the table's partitions (runs SHOW PARTITIONS as a query)
+    #[arg(long)]
+    pub partitions: bool,
+
+    /// Add a column with each type simplified to its family, e.g. 'string' for
+    /// varchar(255) or 'array' for array<struct<...>>
+    #[arg(long)]
+    pub columns_types: bool,
+
+    /// Show the columns and partition keys in separate tables (split) or in a
+    /// single table in table order, marking the partition keys (merged)
+    #[arg(long, value_enum, default_value_t = ColumnsOrder::Split)]
+    pub columns_order: ColumnsOrder,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = DescribeFormat::Table, conflicts_with = "partitions")]
+    pub format: DescribeFormat,
 }
 
 #[derive(Args, Clone)]
 pub struct WorkgroupArgs {
+    /// Filter workgroup names by pattern (e.g. "analytics_*")
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Match the filter pattern case-sensitively
+    #[arg(long)]
+    pub case_sensitive: bool,
+
     /// Maximum number of workgroups to list
     #[arg(short, long, default_value = "50")]
     pub limit: i32,
+
+    /// Show only workgroups in this state (case-insensitive). Disabled
+    /// workgroups can't run queries
+    #[arg(short, long, value_enum, ignore_case = true)]
+    pub state: Option<WorkgroupState>,
+
+    /// Sort the workgroups instead of listing them in the order Athena returns them
+    #[arg(long, value_enum)]
+    pub sort_by: Option<WorkgroupSortBy>,
 }
 
 #[derive(Args, Clone)]
@@ -177,32 +822,221 @@ pub struct HistoryArgs {
     #[arg(short, long)]
     pub limit: Option<i32>,
 
-    /// Show only queries with specific status (SUCCEEDED, FAILED, CANCELLED)
-    #[arg(short, long)]
-    pub status: Option<String>,
+    /// Show only queries with this status (case-insensitive)
+    #[arg(short, long, value_enum, ignore_case = true)]
+    pub status: Option<QueryState>,
+
+    /// Show only queries whose text contains this, ignoring case, e.g. a label
+    /// added with 'query --label' ("tag:team=analytics")
+    #[arg(long, value_name = "TEXT")]
+    pub grep: Option<String>,
+
+    /// Print aggregate statistics (counts by status, data scanned, success rate)
+    /// instead of listing queries
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Group executions of the same query text, showing run counts and total data
+    /// scanned, most expensive first
+    #[arg(long, conflicts_with = "summary")]
+    pub group_by_query: bool,
+
+    /// Read the local log of queries run with this CLI instead of Athena's
+    /// workgroup history (spans workgroups and never expires)
+    #[arg(long, conflicts_with = "group_by_query")]
+    pub local: bool,
+
+    /// Refresh the list every INTERVAL (e.g., "10s", "1m") until Ctrl-C
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_duration, conflicts_with = "local")]
+    pub watch: Option<Duration>,
+
+    /// Output format (defaults to app.history.format, then app.default_format, then table)
+    #[arg(short, long, value_enum)]
+    pub format: Option<ListFormat>,
 }
 
 // For commands that support output
 #[derive(Args, Clone)]
 pub struct InspectArgs {
-    /// Query execution ID to inspect
-    pub query_id: String,
+    /// Query execution ID to inspect. Without it, a recent query is picked
+    /// interactively
+    pub query_id: Option<String>,
 
-    /// Output directory for query results (e.g., "." for current directory)
+    /// Output directory for query results (e.g., "." for current directory),
+    /// a file path such as "results.csv" to use as the full destination,
+    /// or "-" to stream the results to stdout
     #[arg(short, long)]
     pub output: Option<String>,
 
+    /// Name for the downloaded result file (defaults to the name of the S3 object)
+    #[arg(long)]
+    pub filename: Option<String>,
+
     /// Quiet mode - only output the downloaded file path
     #[arg(short, long)]
     pub quiet: bool,
+
+    /// Wait for a queued or running query to finish before showing details
+    #[arg(long)]
+    pub wait: bool,
+
+    /// Give up waiting after this long (e.g., "30s", "10m")
+    #[arg(long, value_parser = parse_duration, requires = "wait")]
+    pub timeout: Option<Duration>,
+
+    /// Also download the query's .metadata file alongside the results
+    #[arg(long)]
+    pub with_metadata: bool,
+
+    /// Decompress gzip-compressed results while downloading
+    #[arg(long)]
+    pub decompress: bool,
+
+    /// Overwrite existing files in the output directory
+    #[arg(long)]
+    pub force: bool,
+
+    /// Always download from S3 instead of reusing locally cached results
+    #[arg(long)]
+    pub no_cache: bool,
 }
 
 #[derive(Args, Clone)]
-pub struct DownloadArgs {
-    /// Query execution ID
+pub struct RerunArgs {
+    /// Execution ID of the query to run again. Without it, a recent query is
+    /// picked interactively
+    pub query_id: Option<String>,
+
+    /// Run against the original execution's database and catalog instead of the
+    /// current ones
+    #[arg(long)]
+    pub same_context: bool,
+
+    /// Query reuse time (e.g., "10m", "2h", "1h30m", "0" to always run fresh)
+    #[arg(short = 'r', long, value_parser = parse_duration, default_value = "60m")]
+    pub reuse_time: Duration,
+}
+
+#[derive(Args, Clone)]
+pub struct CancelArgs {
+    /// Query execution ID to cancel
     pub query_id: String,
+}
 
-    /// Output directory for results
+#[derive(Args, Clone)]
+pub struct CleanupArgs {
+    /// Remove the results of queries in the current workgroup that finished longer
+    /// ago than this (e.g. 30d, 12h)
+    #[arg(long, value_parser = parse_duration)]
+    pub older_than: Duration,
+
+    /// Delete the objects. Without it, only reports what would be deleted
+    #[arg(long)]
+    pub confirm: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct MetricsArgs {
+    /// Workgroup to show metrics for (defaults to the current workgroup)
+    #[arg(value_name = "WORKGROUP")]
+    pub name: Option<String>,
+
+    /// How far back to look (e.g. 1h, 7d)
+    #[arg(long, value_parser = parse_duration, default_value = "1h")]
+    pub since: Duration,
+
+    /// Length of each CloudWatch period, a whole number of minutes
+    #[arg(long, value_parser = parse_duration, default_value = "5m")]
+    pub period: Duration,
+}
+
+#[derive(Args, Clone)]
+pub struct NamedQueryIdArgs {
+    /// Named query ID
+    pub id: String,
+}
+
+#[derive(Args, Clone)]
+pub struct CreateNamedQueryArgs {
+    /// Name to save the query under
+    pub name: String,
+
+    /// SQL query to save
+    pub query: String,
+
+    /// Short description of what the query does
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// Database the query runs against (overrides global settings)
+    #[arg(short = 'n', long)]
+    pub db: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct PrepareArgs {
+    /// Name of the prepared statement
+    pub name: String,
+
+    /// SQL to prepare, with '?' marking each parameter
+    ///
+    /// Example: "SELECT * FROM orders WHERE dt = ? AND region = ?"
+    pub sql: String,
+
+    /// Short description of what the statement does
+    #[arg(long)]
+    pub description: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct PreparedStatementArgs {
+    /// Name of the prepared statement
+    pub name: String,
+}
+
+#[derive(Args, Clone)]
+pub struct ReplArgs {
+    /// Query reuse time applied to every query in the session (e.g., "10m", "0" to disable)
+    #[arg(short = 'r', long, value_parser = parse_duration, default_value = "60m")]
+    pub reuse_time: Duration,
+}
+
+#[derive(Args, Clone)]
+pub struct CompletionArgs {
+    /// Shell to generate completions for
+    ///
+    /// Example: athena-cli completion zsh > _athena-cli
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(Args, Clone)]
+pub struct DownloadArgs {
+    /// Query execution ID. Without it, a recent query is picked interactively
+    pub query_id: Option<String>,
+
+    /// Output directory for results, a file path such as "results.csv"
+    /// to use as the full destination, or "-" to stream the results to stdout
     #[arg(short, long, default_value = ".")]
     pub output: Option<String>,
+
+    /// Name for the downloaded result file (defaults to the name of the S3 object)
+    #[arg(long)]
+    pub filename: Option<String>,
+
+    /// Also download the query's .metadata file alongside the results
+    #[arg(long)]
+    pub with_metadata: bool,
+
+    /// Decompress gzip-compressed results while downloading
+    #[arg(long)]
+    pub decompress: bool,
+
+    /// Overwrite existing files in the output directory
+    #[arg(long)]
+    pub force: bool,
+
+    /// Always download from S3 instead of reusing locally cached results
+    #[arg(long)]
+    pub no_cache: bool,
 }
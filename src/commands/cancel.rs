@@ -0,0 +1,72 @@
+use crate::cli::CancelArgs;
+use crate::context::Context;
+use anyhow::Result;
+use aws_sdk_athena::types::QueryExecutionState;
+use aws_sdk_athena::Client;
+use colored::Colorize;
+
+/// Stops a running query and reports the state it ends up in. Queries that
+/// have already finished are reported as-is rather than treated as an error.
+pub async fn cancel(ctx: &Context, args: &CancelArgs) -> Result<()> {
+    let client = ctx.create_athena_client();
+
+    let state = query_state(&client, &args.query_id).await?;
+    if is_terminal(&state) {
+        if ctx.quiet() {
+            println!("{}", state.as_str());
+        } else {
+            println!(
+                "Query {} has already finished with state {}",
+                args.query_id.bright_green(),
+                state.as_str().yellow()
+            );
+        }
+        return Ok(());
+    }
+
+    client
+        .stop_query_execution()
+        .query_execution_id(&args.query_id)
+        .send()
+        .await?;
+
+    // Cancellation is asynchronous, so the query may still report RUNNING briefly
+    let state = query_state(&client, &args.query_id).await?;
+    if ctx.quiet() {
+        println!("{}", state.as_str());
+    } else if state == QueryExecutionState::Cancelled {
+        println!("Cancelled query {}", args.query_id.bright_green());
+    } else {
+        println!(
+            "Requested cancellation of query {} (current state: {})",
+            args.query_id.bright_green(),
+            state.as_str().yellow()
+        );
+    }
+
+    Ok(())
+}
+
+async fn query_state(client: &Client, query_id: &str) -> Result<QueryExecutionState> {
+    let result = client
+        .get_query_execution()
+        .query_execution_id(query_id)
+        .send()
+        .await?;
+
+    result
+        .query_execution()
+        .and_then(|execution| execution.status())
+        .and_then(|status| status.state())
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("No query execution found with ID: {}", query_id))
+}
+
+fn is_terminal(state: &QueryExecutionState) -> bool {
+    matches!(
+        state,
+        QueryExecutionState::Succeeded
+            | QueryExecutionState::Failed
+            | QueryExecutionState::Cancelled
+    )
+}
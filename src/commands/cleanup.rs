@@ -0,0 +1,209 @@
+//! Deletes old query result files from S3. Athena never removes them, so they
+//! pile up in the output bucket for as long as nobody cleans them up.
+
+use crate::aws::s3::parse_s3_url;
+use crate::cli::CleanupArgs;
+use crate::commands::common::ByteDisplay;
+use crate::context::Context;
+use anyhow::{Context as _, Result};
+use aws_sdk_athena::types::{QueryExecution, QueryExecutionState};
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of IDs accepted by `batch_get_query_execution`
+const EXECUTION_BATCH_SIZE: usize = 50;
+
+/// Maximum number of keys accepted by `delete_objects`
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Finds the result files of queries in the current workgroup that finished
+/// before the `--older-than` cutoff, and deletes them when `--confirm` is given.
+/// Each result file is removed together with its `.metadata` companion.
+pub async fn cleanup(ctx: &Context, args: &CleanupArgs) -> Result<()> {
+    let athena = ctx.create_athena_client();
+    let s3 = ctx.create_s3_client();
+    let workgroup = ctx.workgroup();
+
+    let cutoff = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .saturating_sub(args.older_than)
+        .as_secs() as i64;
+
+    let ids = list_execution_ids(&athena, &workgroup).await?;
+    let mut locations = BTreeSet::new();
+    // A recent query that reused an old one's results points at the old result
+    // file, so anything a recent execution still references is kept
+    let mut in_use = BTreeSet::new();
+    for chunk in ids.chunks(EXECUTION_BATCH_SIZE) {
+        let result = athena
+            .batch_get_query_execution()
+            .set_query_execution_ids(Some(chunk.to_vec()))
+            .send()
+            .await?;
+
+        for execution in result.query_executions() {
+            let Some(location) = execution
+                .result_configuration()
+                .and_then(|c| c.output_location())
+            else {
+                continue;
+            };
+            if finished_before(execution, cutoff) {
+                locations.insert(location.to_string());
+            } else {
+                in_use.insert(location.to_string());
+            }
+        }
+    }
+    locations.retain(|location| !in_use.contains(location));
+
+    // Bucket -> (key, size) of every object belonging to an old result
+    let mut objects: BTreeMap<String, Vec<(String, i64)>> = BTreeMap::new();
+    for location in &locations {
+        let (bucket, key) = parse_s3_url(location)?;
+        let listing = s3
+            .list_objects_v2()
+            .bucket(&bucket)
+            .prefix(&key)
+            .send()
+            .await
+            .context(format!("Failed to list result objects: {}", location))?;
+
+        for object in listing.contents() {
+            if let Some(object_key) = object.key() {
+                if ctx.verbose() {
+                    eprintln!("s3://{}/{}", bucket, object_key);
+                }
+                objects
+                    .entry(bucket.clone())
+                    .or_default()
+                    .push((object_key.to_string(), object.size().unwrap_or(0)));
+            }
+        }
+    }
+
+    let count: usize = objects.values().map(Vec::len).sum();
+    let bytes: i64 = objects.values().flatten().map(|(_, size)| size).sum();
+
+    if count == 0 {
+        println!(
+            "No result objects older than {} found in workgroup: {}",
+            humantime::format_duration(args.older_than),
+            workgroup
+        );
+        return Ok(());
+    }
+
+    if !args.confirm {
+        println!(
+            "Would delete {} objects ({}) from {} query results older than {} in workgroup: {}",
+            count,
+            bytes.format_bytes(),
+            locations.len(),
+            humantime::format_duration(args.older_than),
+            workgroup
+        );
+        println!(
+            "{}",
+            "Dry run, nothing was deleted. Re-run with --confirm to delete them".yellow()
+        );
+        return Ok(());
+    }
+
+    let mut deleted = 0;
+    let mut freed: i64 = 0;
+    for (bucket, bucket_objects) in &objects {
+        for chunk in bucket_objects.chunks(DELETE_BATCH_SIZE) {
+            let identifiers = chunk
+                .iter()
+                .map(|(key, _)| ObjectIdentifier::builder().key(key).build())
+                .collect::<Result<Vec<_>, _>>()?;
+            let delete = Delete::builder()
+                .set_objects(Some(identifiers))
+                .quiet(true)
+                .build()?;
+
+            let result = s3
+                .delete_objects()
+                .bucket(bucket)
+                .delete(delete)
+                .send()
+                .await
+                .context(format!("Failed to delete objects from bucket: {}", bucket))?;
+
+            // In quiet mode S3 only reports the keys it failed to delete
+            let failed: BTreeSet<&str> = result.errors().iter().filter_map(|e| e.key()).collect();
+            for error in result.errors() {
+                eprintln!(
+                    "{} s3://{}/{}: {}",
+                    "Failed to delete".red(),
+                    bucket,
+                    error.key().unwrap_or("-"),
+                    error.message().unwrap_or("unknown error")
+                );
+            }
+
+            for (key, size) in chunk {
+                if !failed.contains(key.as_str()) {
+                    deleted += 1;
+                    freed += size;
+                }
+            }
+        }
+    }
+
+    println!(
+        "Deleted {} objects, freeing {}",
+        deleted.to_string().bright_green(),
+        freed.format_bytes()
+    );
+    Ok(())
+}
+
+/// Lists the IDs of every query execution Athena still remembers in a workgroup
+async fn list_execution_ids(
+    client: &aws_sdk_athena::Client,
+    workgroup: &str,
+) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let result = client
+            .list_query_executions()
+            .work_group(workgroup)
+            .set_next_token(next_token)
+            .send()
+            .await?;
+
+        ids.extend(result.query_execution_ids().iter().cloned());
+
+        next_token = result.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(ids)
+}
+
+/// Whether an execution has finished, and did so before `cutoff` (Unix seconds).
+/// Running queries are never touched, since their results are still being written.
+fn finished_before(execution: &QueryExecution, cutoff: i64) -> bool {
+    let Some(status) = execution.status() else {
+        return false;
+    };
+
+    matches!(
+        status.state(),
+        Some(
+            QueryExecutionState::Succeeded
+                | QueryExecutionState::Failed
+                | QueryExecutionState::Cancelled
+        )
+    ) && status
+        .completion_date_time()
+        .is_some_and(|completed| completed.secs() < cutoff)
+}
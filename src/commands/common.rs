@@ -1,5 +1,10 @@
+use crate::config::{self, Timezone};
 use aws_sdk_athena::primitives::DateTime;
+use aws_sdk_athena::types::QueryExecution;
 use byte_unit::Byte;
+use chrono::{Local, Utc};
+use prettytable::Cell;
+use std::fmt;
 use std::time::Duration;
 
 // Trait for converting values to display strings with a default fallback
@@ -40,16 +45,49 @@ impl DisplayValue for i64 {
     }
 }
 
-// DateTime formatting
-impl DisplayValue for DateTime {
-    fn to_display_value(&self) -> String {
-        self.to_string()
+// Settings for rendering timestamps, resolved from the app config
+#[derive(Debug, Clone)]
+pub struct TimestampFormat {
+    pub format: String,
+    pub timezone: Timezone,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self {
+            format: config::default_date_format(),
+            timezone: Timezone::default(),
+        }
     }
 }
 
-impl DisplayValue for &DateTime {
-    fn to_display_value(&self) -> String {
-        (*self).to_string()
+// DateTime formatting with a configurable format and timezone
+pub trait DateTimeFormat {
+    fn format_datetime(&self, format: &TimestampFormat) -> String;
+}
+
+impl DateTimeFormat for DateTime {
+    fn format_datetime(&self, format: &TimestampFormat) -> String {
+        let Some(utc) = chrono::DateTime::<Utc>::from_timestamp(self.secs(), self.subsec_nanos())
+        else {
+            return self.to_string();
+        };
+
+        match format.timezone {
+            Timezone::Utc => utc.format(&format.format).to_string(),
+            Timezone::Local => utc.with_timezone(&Local).format(&format.format).to_string(),
+        }
+    }
+}
+
+pub trait OptionDateTimeFormat {
+    fn format_datetime_or_default(&self, format: &TimestampFormat) -> String;
+}
+
+impl OptionDateTimeFormat for Option<&DateTime> {
+    fn format_datetime_or_default(&self, format: &TimestampFormat) -> String {
+        self.map(|dt| dt.format_datetime(format))
+            .unwrap_or_else(|| "-".to_string())
     }
 }
 
@@ -109,3 +147,265 @@ impl<T: Into<i64> + Copy> OptionDurationFormat for Option<T> {
             .unwrap_or_else(|| "-".to_string())
     }
 }
+
+/// Athena bills scanned data in whole megabytes, with a 10 MB minimum per query
+const BILLING_INCREMENT_BYTES: i64 = 1 << 20;
+const MIN_BILLED_BYTES: i64 = 10 << 20;
+const BYTES_PER_TB: f64 = (1u64 << 40) as f64;
+
+/// Estimated cost in dollars of scanning `bytes` at `price_per_tb`. Queries
+/// that scan nothing (DDL, failed queries) are free.
+pub fn estimate_scan_cost(bytes: i64, price_per_tb: f64) -> f64 {
+    if bytes <= 0 {
+        return 0.0;
+    }
+
+    let billed =
+        (bytes + BILLING_INCREMENT_BYTES - 1) / BILLING_INCREMENT_BYTES * BILLING_INCREMENT_BYTES;
+    billed.max(MIN_BILLED_BYTES) as f64 / BYTES_PER_TB * price_per_tb
+}
+
+/// Estimated cost of an execution. Results reused from Athena's cache cost nothing.
+pub fn execution_cost(execution: &QueryExecution, price_per_tb: f64) -> Option<f64> {
+    if used_result_cache(execution) {
+        return Some(0.0);
+    }
+    execution
+        .statistics()?
+        .data_scanned_in_bytes()
+        .map(|bytes| estimate_scan_cost(bytes, price_per_tb))
+}
+
+/// Formats a dollar amount, keeping enough decimals to show sub-cent costs
+pub fn format_cost(cost: f64) -> String {
+    if cost == 0.0 || cost >= 0.01 {
+        format!("${:.2}", cost)
+    } else if cost >= 0.0001 {
+        format!("${:.4}", cost)
+    } else {
+        "<$0.0001".to_string()
+    }
+}
+
+/// Athena engine version that ran a query, e.g. "Athena engine version 3"
+pub fn engine_version(execution: &QueryExecution) -> Option<&str> {
+    execution
+        .engine_version()
+        .and_then(|version| version.effective_engine_version())
+}
+
+/// Marks a table value that was cut off
+const ELLIPSIS: &str = "...";
+
+/// Shortens `value` to at most `max_width` characters, ending it with "..." when
+/// it's cut. A `max_width` of 0 leaves the value as it is.
+pub fn truncate_value(value: &str, max_width: usize) -> String {
+    if max_width == 0 || value.chars().count() <= max_width {
+        return value.to_string();
+    }
+
+    // Too narrow for an ellipsis to leave anything readable
+    if max_width <= ELLIPSIS.len() {
+        return value.chars().take(max_width).collect();
+    }
+
+    let kept: String = value.chars().take(max_width - ELLIPSIS.len()).collect();
+    format!("{}{}", kept, ELLIPSIS)
+}
+
+/// Builds a table cell for `value`, shortened with [`truncate_value`] so a single
+/// long value can't stretch the whole table
+pub fn table_cell(value: &str, max_width: usize) -> Cell {
+    Cell::new(&truncate_value(value, max_width))
+}
+
+/// Whether Athena answered a query from a previous execution's results
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultReuse {
+    /// Results were reused, from the given execution when it can be identified
+    Reused(Option<String>),
+    /// The query ran fresh
+    Fresh,
+}
+
+impl ResultReuse {
+    /// Reads Athena's result reuse flag; `None` when the execution doesn't report it
+    pub fn from_execution(execution: &QueryExecution) -> Option<Self> {
+        let info = execution.statistics()?.result_reuse_information()?;
+        if !info.reused_previous_result() {
+            return Some(Self::Fresh);
+        }
+
+        // Athena doesn't return the reused execution's ID, but a reused query points
+        // at the earlier execution's result file, which is named after that execution
+        let reused_id = execution
+            .result_configuration()
+            .and_then(|c| c.output_location())
+            .and_then(execution_id_from_location)
+            .filter(|id| Some(*id) != execution.query_execution_id())
+            .map(str::to_string);
+
+        Some(Self::Reused(reused_id))
+    }
+}
+
+impl fmt::Display for ResultReuse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reused(Some(id)) => write!(f, "Reused results of {}", id),
+            Self::Reused(None) => write!(f, "Reused previous results"),
+            Self::Fresh => write!(f, "Fresh execution"),
+        }
+    }
+}
+
+/// Whether a query's results came from Athena's result cache
+pub fn used_result_cache(execution: &QueryExecution) -> bool {
+    let statistics = execution.statistics();
+    is_cache_hit(
+        statistics
+            .and_then(|s| s.result_reuse_information())
+            .map(|info| info.reused_previous_result()),
+        statistics.and_then(|s| s.data_scanned_in_bytes()),
+    )
+}
+
+/// Athena's reuse flag is authoritative, since a fresh scan of a tiny partition can
+/// also report zero bytes. Zero scanned bytes only count as a cache hit for
+/// executions that don't report the flag.
+fn is_cache_hit(reused_previous_result: Option<bool>, data_scanned: Option<i64>) -> bool {
+    reused_previous_result.unwrap_or(data_scanned == Some(0))
+}
+
+/// Extracts the execution ID from an Athena result location such as
+/// `s3://bucket/prefix/<execution-id>.csv`
+fn execution_id_from_location(location: &str) -> Option<&str> {
+    let file_name = location.rsplit('/').next()?;
+    let stem = file_name.split('.').next()?;
+
+    // Execution IDs are UUIDs
+    let is_uuid = stem.len() == 36 && stem.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+    is_uuid.then_some(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_value() {
+        let query = "SELECT * FROM orders";
+        assert_eq!(truncate_value(query, 0), query);
+        assert_eq!(truncate_value(query, 20), query);
+        assert_eq!(truncate_value(query, 19), "SELECT * FROM or...");
+        assert_eq!(truncate_value(query, 4), "S...");
+        assert_eq!(truncate_value(query, 3), "SEL");
+        assert_eq!(truncate_value("", 5), "");
+
+        // Widths count characters, not bytes
+        assert_eq!(truncate_value("Zürich, Genève", 10), "Zürich,...");
+    }
+
+    #[test]
+    fn test_truncate_value_multibyte_at_boundary() {
+        // Byte 9 falls inside '日', where slicing the string would panic
+        let query = "SELECT '日本語テキスト' FROM t";
+        assert_eq!(truncate_value(query, 12), "SELECT '日...");
+        assert_eq!(truncate_value(query, 10), "SELECT ...");
+
+        let emoji = "SELECT '🚀🚀🚀🚀'";
+        assert_eq!(truncate_value(emoji, 11), "SELECT '...");
+        assert_eq!(truncate_value(emoji, 12), "SELECT '🚀...");
+    }
+
+    #[test]
+    fn test_byte_formatting_is_consistent() {
+        for bytes in [0_i64, 999, 1_500, 2_340_000, 7_000_000_000] {
+            assert_eq!(bytes.format_bytes(), Some(bytes).format_bytes_or_default());
+        }
+
+        assert_eq!(1_500_i64.format_bytes(), "1.5 KB");
+        assert_eq!(None::<i64>.format_bytes_or_default(), "-");
+    }
+
+    #[test]
+    fn test_format_datetime_utc() {
+        let format = TimestampFormat {
+            format: "%Y-%m-%d %H:%M:%S".to_string(),
+            timezone: Timezone::Utc,
+        };
+
+        let dt = DateTime::from_secs(1_700_000_000);
+        assert_eq!(dt.format_datetime(&format), "2023-11-14 22:13:20");
+
+        let custom = TimestampFormat {
+            format: "%d/%m/%Y".to_string(),
+            timezone: Timezone::Utc,
+        };
+        assert_eq!(dt.format_datetime(&custom), "14/11/2023");
+
+        let missing: Option<&DateTime> = None;
+        assert_eq!(missing.format_datetime_or_default(&format), "-");
+    }
+
+    #[test]
+    fn test_estimate_scan_cost() {
+        const MB: i64 = 1 << 20;
+        const TB: i64 = 1 << 40;
+
+        assert_eq!(estimate_scan_cost(0, 5.0), 0.0);
+        assert_eq!(estimate_scan_cost(TB, 5.0), 5.0);
+        assert_eq!(estimate_scan_cost(2 * TB, 6.75), 13.5);
+
+        // Small scans are billed as 10 MB, and partial megabytes round up
+        assert_eq!(estimate_scan_cost(1, 5.0), estimate_scan_cost(10 * MB, 5.0));
+        assert_eq!(
+            estimate_scan_cost(20 * MB + 1, 5.0),
+            estimate_scan_cost(21 * MB, 5.0)
+        );
+    }
+
+    #[test]
+    fn test_format_cost() {
+        assert_eq!(format_cost(0.0), "$0.00");
+        assert_eq!(format_cost(12.345), "$12.35");
+        assert_eq!(format_cost(0.0477), "$0.05");
+        assert_eq!(format_cost(0.000048), "<$0.0001");
+        assert_eq!(format_cost(0.0012), "$0.0012");
+    }
+
+    #[test]
+    fn test_is_cache_hit() {
+        // Reused results are a hit whatever the scanned bytes
+        assert!(is_cache_hit(Some(true), Some(0)));
+        assert!(is_cache_hit(Some(true), None));
+
+        // A fresh scan that read next to nothing is not
+        assert!(!is_cache_hit(Some(false), Some(0)));
+        assert!(!is_cache_hit(Some(false), Some(1_024)));
+
+        // Without the flag, fall back to the scanned-bytes heuristic
+        assert!(is_cache_hit(None, Some(0)));
+        assert!(!is_cache_hit(None, Some(1_024)));
+        assert!(!is_cache_hit(None, None));
+    }
+
+    #[test]
+    fn test_execution_id_from_location() {
+        let id = "0b4d4e5c-3f1a-4c2b-9d8e-7f6a5b4c3d2e";
+
+        assert_eq!(
+            execution_id_from_location(&format!("s3://results/prefix/{}.csv", id)),
+            Some(id)
+        );
+        assert_eq!(
+            execution_id_from_location(&format!("s3://results/{}.txt", id)),
+            Some(id)
+        );
+        assert_eq!(
+            execution_id_from_location("s3://results/unload/part-0.parquet"),
+            None
+        );
+        assert_eq!(execution_id_from_location("s3://results/"), None);
+    }
+}
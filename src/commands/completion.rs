@@ -0,0 +1,14 @@
+use crate::cli::{Cli, CompletionArgs};
+use anyhow::Result;
+use clap::CommandFactory;
+use std::io;
+
+/// Writes a completion script for the requested shell to stdout
+pub fn generate(args: &CompletionArgs) -> Result<()> {
+    let mut cmd = Cli::command();
+    let bin_name = cmd.get_name().to_string();
+
+    clap_complete::generate(args.shell, &mut cmd, bin_name, &mut io::stdout());
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use crate::aws::{self, s3::validate_output_location};
+use crate::cache::ResultCache;
+use crate::cli::{AwsArgs, ConfigInitArgs};
+use crate::config::{self, Config};
+use crate::context::{Context, Setting};
+use crate::error::AthenaCliError;
+use crate::utils::table_style;
+use anyhow::{bail, Result};
+use aws_sdk_athena::Client;
+use colored::Colorize;
+use dialoguer::{Input, Select};
+use prettytable::{Cell, Row, Table};
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Removes every locally cached query result
+pub fn clear_cache() -> Result<()> {
+    let cleared = ResultCache::open()?.clear()?;
+
+    if cleared == 0 {
+        println!("Cache is already empty");
+    } else {
+        println!(
+            "Cleared cached results for {} {}",
+            cleared.to_string().bright_green(),
+            if cleared == 1 { "query" } else { "queries" }
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints every AWS setting with its effective value and where that value came
+/// from, to explain e.g. why queries go to an unexpected workgroup
+pub fn resolve(ctx: &Context) -> Result<()> {
+    let settings = [
+        (
+            "Profile",
+            ctx.resolve_profile(),
+            "(default credential chain)",
+        ),
+        ("Region", Some(ctx.resolve_region()), ""),
+        ("Workgroup", Some(ctx.resolve_workgroup()), ""),
+        ("Catalog", Some(ctx.resolve_catalog()), ""),
+        ("Database", ctx.resolve_database(), "-"),
+        (
+            "Output Location",
+            ctx.resolve_output_location(),
+            "(workgroup setting)",
+        ),
+    ];
+
+    println!("Config file: {}", config::get_config_path()?.display());
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Setting").style_spec("Fb"),
+        Cell::new("Value").style_spec("Fb"),
+        Cell::new("Source").style_spec("Fb"),
+    ]));
+    for (label, setting, unset) in settings {
+        let (value, source) = match setting {
+            Some(Setting { value, source }) => (value, source.to_string()),
+            None => (unset.to_string(), "default".to_string()),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(label),
+            Cell::new(&value),
+            Cell::new(&source),
+        ]));
+    }
+
+    table_style::print(&table);
+    Ok(())
+}
+
+/// Asks for the AWS settings and writes them to a new config file. App settings
+/// keep their defaults and can be edited in the file afterwards.
+///
+/// When the credentials work, workgroups, catalogs and databases are offered as
+/// a list to pick from; otherwise their names are typed in.
+pub async fn init(args: &ConfigInitArgs, aws_args: &AwsArgs) -> Result<()> {
+    let path = config::get_config_path()?;
+    if path.exists() && !args.force {
+        bail!(
+            "Config already exists at {}. Use --force to overwrite it",
+            path.display()
+        );
+    }
+    if !io::stdin().is_terminal() {
+        bail!("config init asks questions, so it needs an interactive terminal");
+    }
+
+    let mut config = Config::default();
+    let settings = &mut config.aws;
+
+    let default_region = aws_args
+        .region
+        .clone()
+        .or_else(|| env::var("AWS_REGION").ok())
+        .or_else(|| env::var("AWS_DEFAULT_REGION").ok())
+        .or(settings.region.take())
+        .unwrap_or_default();
+    let region = ask("AWS region", &default_region, false)?;
+
+    let profile = aws_args
+        .profile
+        .clone()
+        .or_else(|| env::var("AWS_PROFILE").ok())
+        .or_else(|| env::var("AWS_DEFAULT_PROFILE").ok());
+    let ca_bundle = env::var("AWS_CA_BUNDLE").ok().filter(|p| !p.is_empty());
+    let sdk_config = aws::build_aws_config(profile, region.clone(), ca_bundle).await?;
+    let mut discovery = Discovery {
+        client: Some(Client::new(&sdk_config)),
+    };
+    settings.region = Some(region);
+
+    let workgroups = discovery.workgroups().await;
+    settings.workgroup = Some(choose(
+        "Workgroup",
+        workgroups,
+        settings.workgroup.as_deref().unwrap_or("primary"),
+        false,
+    )?);
+    settings.output_location = Input::new()
+        .with_prompt("Query result location (empty to use the workgroup's)")
+        .allow_empty(true)
+        .validate_with(|location: &String| {
+            if location.is_empty() {
+                Ok(())
+            } else {
+                validate_output_location(location).map_err(|e| e.to_string())
+            }
+        })
+        .interact_text()?;
+
+    let catalogs = discovery.catalogs().await;
+    let catalog = choose(
+        "Data catalog",
+        catalogs,
+        settings.catalog.as_deref().unwrap_or("AwsDataCatalog"),
+        false,
+    )?;
+    let databases = discovery.databases(&catalog).await;
+    settings.catalog = Some(catalog);
+    settings.database = Some(choose("Default database", databases, "", true)?)
+        .filter(|database| !database.is_empty());
+
+    config.save(&path)?;
+    println!(
+        "Saved config to {}",
+        path.display().to_string().bright_green()
+    );
+    Ok(())
+}
+
+/// Looks up the names offered by `config init`. Only the first page of each
+/// listing is fetched. After a failed call (usually missing credentials) the
+/// client is dropped, so the remaining prompts ask for names without waiting on AWS.
+struct Discovery {
+    client: Option<Client>,
+}
+
+/// Page size of the listings, the maximum all three APIs accept
+const DISCOVERY_PAGE_SIZE: i32 = 50;
+
+impl Discovery {
+    async fn workgroups(&mut self) -> Option<Vec<String>> {
+        let client = self.client.as_ref()?;
+        let result = client
+            .list_work_groups()
+            .max_results(DISCOVERY_PAGE_SIZE)
+            .send()
+            .await
+            .map(|r| {
+                r.work_groups()
+                    .iter()
+                    .filter_map(|w| w.name().map(str::to_string))
+                    .collect()
+            })
+            .map_err(AthenaCliError::from_sdk);
+        self.found("workgroups", result)
+    }
+
+    async fn catalogs(&mut self) -> Option<Vec<String>> {
+        let client = self.client.as_ref()?;
+        let result = client
+            .list_data_catalogs()
+            .max_results(DISCOVERY_PAGE_SIZE)
+            .send()
+            .await
+            .map(|r| {
+                r.data_catalogs_summary()
+                    .iter()
+                    .filter_map(|c| c.catalog_name().map(str::to_string))
+                    .collect()
+            })
+            .map_err(AthenaCliError::from_sdk);
+        self.found("data catalogs", result)
+    }
+
+    async fn databases(&mut self, catalog: &str) -> Option<Vec<String>> {
+        let client = self.client.as_ref()?;
+        let result = client
+            .list_databases()
+            .catalog_name(catalog)
+            .max_results(DISCOVERY_PAGE_SIZE)
+            .send()
+            .await
+            .map(|r| {
+                r.database_list()
+                    .iter()
+                    .map(|d| d.name().to_string())
+                    .collect()
+            })
+            .map_err(AthenaCliError::from_sdk);
+        self.found("databases", result)
+    }
+
+    fn found(
+        &mut self,
+        what: &str,
+        result: Result<Vec<String>, AthenaCliError>,
+    ) -> Option<Vec<String>> {
+        match result {
+            Ok(names) if !names.is_empty() => Some(names),
+            Ok(_) => None,
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Couldn't list {}, so names have to be typed in: {}",
+                        what, err
+                    )
+                    .yellow()
+                );
+                self.client = None;
+                None
+            }
+        }
+    }
+}
+
+/// Lets the user pick one of `choices` or type another name. Without choices
+/// this is the same as [`ask`].
+fn choose(
+    prompt: &str,
+    choices: Option<Vec<String>>,
+    default: &str,
+    optional: bool,
+) -> Result<String> {
+    const NONE: &str = "(none)";
+    const OTHER: &str = "(type another name)";
+
+    let Some(mut items) = choices else {
+        return ask(prompt, default, optional);
+    };
+    if optional {
+        items.insert(0, NONE.to_string());
+    }
+    items.push(OTHER.to_string());
+
+    let selected = Select::new()
+        .with_prompt(prompt)
+        .items(&items)
+        .default(items.iter().position(|item| item == default).unwrap_or(0))
+        .interact()?;
+
+    match items[selected].as_str() {
+        NONE => Ok(String::new()),
+        OTHER => ask(prompt, default, optional),
+        name => Ok(name.to_string()),
+    }
+}
+
+/// Prompts for a single value, offering `default` when it isn't empty
+fn ask(prompt: &str, default: &str, optional: bool) -> Result<String> {
+    let prompt = if optional {
+        format!("{} (empty for none)", prompt)
+    } else {
+        prompt.to_string()
+    };
+    let mut input = Input::<String>::new()
+        .with_prompt(prompt)
+        .allow_empty(optional);
+    if !default.is_empty() {
+        input = input.default(default.to_string());
+    }
+
+    Ok(input.interact_text()?.trim().to_string())
+}
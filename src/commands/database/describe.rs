@@ -1,37 +1,61 @@
+use super::tables::{is_table_not_found, table_names};
+use super::utils::display::header_row;
 use super::utils::{ColumnDisplay, ParameterDisplay};
-use crate::cli::DescribeTableArgs;
+use crate::cli::{ColumnsOrder, DescribeFormat, DescribeTableArgs};
+use crate::commands::common::ByteDisplay;
+use crate::commands::query;
 use crate::context::Context;
-use anyhow::{Context as _, Result};
+use crate::error::AthenaCliError;
+use crate::utils::suggest::closest_matches;
+use crate::utils::table_style;
+use anyhow::Result;
+use aws_sdk_athena::types::{Column, TableMetadata};
+use aws_sdk_athena::Client;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
 pub async fn describe_table(ctx: &Context, args: &DescribeTableArgs) -> Result<()> {
     let client = ctx.create_athena_client();
 
-    // Parse database and table names
-    let (database_name, table_name) = if args.table.contains('.') {
-        let parts: Vec<&str> = args.table.splitn(2, '.').collect();
-        (parts[0].to_string(), parts[1].to_string())
-    } else if let Some(db) = args.db.as_ref() {
-        (db.clone(), args.table.clone())
-    } else if let Some(db) = ctx.database().as_ref() {
-        (db.clone(), args.table.clone())
-    } else {
-        anyhow::bail!("No database specified. Use --db or 'database.table' format")
+    // Parse catalog, database and table names
+    let reference = parse_table_reference(&args.table)?;
+    let catalog_name = reference.catalog.unwrap_or_else(|| ctx.catalog());
+    let database_name = match reference.database.or_else(|| args.db.clone()) {
+        Some(db) => db,
+        None => ctx.database().ok_or_else(|| {
+            anyhow::anyhow!("No database specified. Use --db or 'database.table' format")
+        })?,
     };
+    let table_name = reference.table;
 
     // Get table metadata
-    let result = client
+    let result = match client
         .get_table_metadata()
-        .catalog_name(ctx.catalog())
+        .catalog_name(&catalog_name)
         .database_name(&database_name)
         .table_name(&table_name)
         .send()
         .await
-        .with_context(|| {
-            format!(
+    {
+        Ok(result) => result,
+        Err(err) => {
+            let err = if is_table_not_found(&err) {
+                suggest_tables(&client, &catalog_name, &database_name, &table_name).await;
+                AthenaCliError::NotFound(format!(
+                    "Table {}.{} not found",
+                    database_name, table_name
+                ))
+            } else {
+                AthenaCliError::from_sdk(err)
+            };
+            return Err(anyhow::Error::new(err).context(format!(
                 "Failed to get metadata for table {}.{}",
                 database_name, table_name
-            )
-        })?;
+            )));
+        }
+    };
 
     let table_metadata = result.table_metadata().ok_or_else(|| {
         anyhow::anyhow!(
@@ -41,6 +65,12 @@ pub async fn describe_table(ctx: &Context, args: &DescribeTableArgs) -> Result<(
         )
     })?;
 
+    if args.format == DescribeFormat::Json {
+        let schema = TableSchema::new(&catalog_name, &database_name, table_metadata);
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
     // Display table info
     println!("Table: {}.{}", database_name, table_name);
     println!();
@@ -58,18 +88,51 @@ pub async fn describe_table(ctx: &Context, args: &DescribeTableArgs) -> Result<(
         println!("Description: {}", description);
     }
 
+    // Display size estimates recorded by Glue/Athena
+    println!("\nSize Estimates:");
+    let stats = table_metadata
+        .parameters()
+        .map(TableStats::from_parameters)
+        .unwrap_or_default();
+    if stats.is_empty() {
+        println!("No row count or size statistics recorded for this table");
+    } else {
+        if let Some(rows) = stats.num_rows {
+            println!("Rows: {}", rows);
+        }
+        if let Some(size) = stats.total_size {
+            println!("Total Size: {}", size.format_bytes());
+        }
+        if let Some(size) = stats.raw_data_size {
+            println!("Raw Data Size: {}", size.format_bytes());
+        }
+    }
+
     // Display columns
     let columns = table_metadata.columns();
-    println!("\nColumns: (found {})", columns.len());
-    if !columns.is_empty() {
-        let table = ColumnDisplay::create_columns_table(columns);
-        table.printstd();
+    let partitions = table_metadata.partition_keys();
+    let merged = args.columns_order == ColumnsOrder::Merged;
+    if merged {
+        println!(
+            "\nColumns: (found {}, including {} partition keys)",
+            columns.len() + partitions.len(),
+            partitions.len()
+        );
     } else {
+        println!("\nColumns: (found {})", columns.len());
+    }
+    if columns.is_empty() && (!merged || partitions.is_empty()) {
         println!("No columns found in table metadata");
+    } else if merged {
+        let table =
+            ColumnDisplay::create_merged_columns_table(columns, partitions, args.columns_types);
+        table_style::print(&table);
+    } else {
+        let table = ColumnDisplay::create_columns_table(columns, args.columns_types);
+        table_style::print(&table);
     }
 
     // Display partitions
-    let partitions = table_metadata.partition_keys();
 
     // Always show partition information
     println!("\nPartition Details:");
@@ -78,20 +141,513 @@ pub async fn describe_table(ctx: &Context, args: &DescribeTableArgs) -> Result<(
     } else {
         println!("Table has {} partition keys", partitions.len());
 
-        // Display partition keys in a table
-        let table = ColumnDisplay::create_columns_table(partitions);
-        table.printstd();
+        // Display partition keys in a table, unless they were listed with the columns
+        if !merged {
+            let table = ColumnDisplay::create_columns_table(partitions, args.columns_types);
+            table_style::print(&table);
+        }
 
-        println!("\nDetailed partition information is available through SQL with:");
-        println!("SHOW PARTITIONS {}.{}", database_name, table_name);
+        if args.partitions {
+            print_partitions(ctx, &client, &database_name, &table_name, partitions).await?;
+        } else {
+            println!("\nDetailed partition information is available with --partitions, or through SQL with:");
+            println!("SHOW PARTITIONS {}.{}", database_name, table_name);
+        }
     }
 
     // Display storage parameters
     if let Some(parameters) = table_metadata.parameters() {
         println!("\nStorage Parameters:");
         let table = ParameterDisplay::create_parameters_table(parameters, &["comment"]);
-        table.printstd();
+        table_style::print(&table);
+    }
+
+    Ok(())
+}
+
+/// Prints the tables in `database` with names close to `table_name`. Nothing is
+/// printed if listing the tables fails, leaving just the original error.
+async fn suggest_tables(
+    client: &Client,
+    catalog_name: &str,
+    database_name: &str,
+    table_name: &str,
+) {
+    let Ok(names) = table_names(client, catalog_name, database_name).await else {
+        return;
+    };
+
+    let suggestions = closest_matches(table_name, names.iter().map(String::as_str));
+    if !suggestions.is_empty() {
+        eprintln!(
+            "Table {}.{} not found. Did you mean: {}?",
+            database_name,
+            table_name,
+            suggestions.join(", ").bright_green()
+        );
+    }
+}
+
+/// Runs `SHOW PARTITIONS` for the table and prints one row per partition with a
+/// column for each partition key
+async fn print_partitions(
+    ctx: &Context,
+    client: &Client,
+    database_name: &str,
+    table_name: &str,
+    partition_keys: &[Column],
+) -> Result<()> {
+    let statement = format!(
+        "SHOW PARTITIONS `{}`.`{}`",
+        database_name.replace('`', "``"),
+        table_name.replace('`', "``")
+    );
+    let query_id = query::start_query(
+        client,
+        database_name,
+        &statement,
+        &ctx.workgroup(),
+        Duration::ZERO,
+        ctx.output_location().as_deref(),
+    )
+    .await?;
+    query::wait_for_query(client, &query_id).await?;
+    let partitions = query::first_column_values(client, &query_id).await?;
+
+    println!("\nPartitions: (found {})", partitions.len());
+    if partitions.is_empty() {
+        println!("Table has no partitions yet");
+        return Ok(());
+    }
+
+    let keys: Vec<&str> = partition_keys.iter().map(|key| key.name()).collect();
+    let mut table = prettytable::Table::new();
+    table.add_row(header_row(&keys));
+    for partition in &partitions {
+        let values = partition_values(partition);
+        table.add_row(prettytable::Row::new(
+            keys.iter()
+                .map(|key| {
+                    let value = values
+                        .iter()
+                        .find(|(name, _)| name == key)
+                        .map_or("-", |(_, value)| value.as_str());
+                    prettytable::Cell::new(value)
+                })
+                .collect(),
+        ));
     }
+    table_style::print(&table);
 
     Ok(())
 }
+
+/// Splits a Hive-style partition spec such as `dt=2024-01-01/region=us` into
+/// key/value pairs
+fn partition_values(partition: &str) -> Vec<(String, String)> {
+    partition
+        .split('/')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Row count and size estimates stored in a table's parameters
+#[derive(Debug, Default, PartialEq)]
+struct TableStats {
+    num_rows: Option<i64>,
+    total_size: Option<i64>,
+    raw_data_size: Option<i64>,
+}
+
+impl TableStats {
+    fn from_parameters(parameters: &HashMap<String, String>) -> Self {
+        // Negative values (usually -1) mean the statistic was never computed
+        let get = |key: &str| {
+            parameters
+                .get(key)
+                .and_then(|value| value.trim().parse::<i64>().ok())
+                .filter(|value| *value >= 0)
+        };
+
+        Self {
+            num_rows: get("numRows"),
+            total_size: get("totalSize"),
+            raw_data_size: get("rawDataSize"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A table's structure as printed by `--format json`
+#[derive(Debug, Serialize)]
+struct TableSchema {
+    catalog: String,
+    database: String,
+    name: String,
+    table_type: Option<String>,
+    create_time: Option<String>,
+    columns: Vec<ColumnSchema>,
+    partition_keys: Vec<ColumnSchema>,
+    /// Sorted by key so the output is stable
+    parameters: BTreeMap<String, String>,
+}
+
+impl TableSchema {
+    fn new(catalog: &str, database: &str, table: &TableMetadata) -> Self {
+        Self {
+            catalog: catalog.to_string(),
+            database: database.to_string(),
+            name: table.name().to_string(),
+            table_type: table.table_type().map(str::to_string),
+            create_time: table.create_time().map(|time| time.to_string()),
+            columns: table.columns().iter().map(ColumnSchema::from).collect(),
+            partition_keys: table
+                .partition_keys()
+                .iter()
+                .map(ColumnSchema::from)
+                .collect(),
+            parameters: table
+                .parameters()
+                .map(|parameters| {
+                    parameters
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A column with its type both as Athena reports it and broken down into
+/// nested element, key/value and field types
+#[derive(Debug, Serialize)]
+struct ColumnSchema {
+    name: String,
+    #[serde(rename = "type")]
+    column_type: String,
+    type_info: ColumnType,
+    comment: Option<String>,
+}
+
+impl From<&Column> for ColumnSchema {
+    fn from(column: &Column) -> Self {
+        let column_type = column.r#type().unwrap_or_default().to_string();
+        Self {
+            name: column.name().to_string(),
+            type_info: ColumnType::parse(&column_type),
+            column_type,
+            comment: column.comment().map(str::to_string),
+        }
+    }
+}
+
+/// An Athena (Hive DDL) column type such as `bigint`, `decimal(10,2)`,
+/// `array<string>` or `struct<id:int,tags:map<string,string>>`
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ColumnType {
+    /// A scalar type, kept verbatim including any precision, e.g. `varchar(10)`
+    Primitive {
+        name: String,
+    },
+    Array {
+        element: Box<ColumnType>,
+    },
+    Map {
+        key: Box<ColumnType>,
+        value: Box<ColumnType>,
+    },
+    Struct {
+        fields: Vec<StructField>,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct StructField {
+    name: String,
+    #[serde(rename = "type")]
+    field_type: ColumnType,
+}
+
+impl ColumnType {
+    /// Parses a type string. Anything that isn't a well-formed array, map or
+    /// struct is kept as a primitive with the original text, so no type is lost.
+    fn parse(type_string: &str) -> Self {
+        let trimmed = type_string.trim();
+        Self::parse_complex(trimmed).unwrap_or_else(|| Self::Primitive {
+            name: trimmed.to_string(),
+        })
+    }
+
+    fn parse_complex(type_string: &str) -> Option<Self> {
+        let (kind, inner) = type_string.split_once('<')?;
+        let inner = inner.strip_suffix('>')?;
+        let parts = split_top_level(inner);
+
+        match (kind.trim().to_lowercase().as_str(), parts.as_slice()) {
+            ("array", [element]) => Some(Self::Array {
+                element: Box::new(Self::parse(element)),
+            }),
+            ("map", [key, value]) => Some(Self::Map {
+                key: Box::new(Self::parse(key)),
+                value: Box::new(Self::parse(value)),
+            }),
+            ("struct", fields) => fields
+                .iter()
+                .map(|field| {
+                    let (name, field_type) = field.split_once(':')?;
+                    Some(StructField {
+                        name: name.trim().to_string(),
+                        field_type: Self::parse(field_type),
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|fields| Self::Struct { fields }),
+            _ => None,
+        }
+    }
+}
+
+/// Splits on commas that aren't nested inside `<...>` or `(...)`, so
+/// `string,map<string,int>,decimal(10,2)` gives three parts
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// A table as written on the command line, with optional catalog and database
+#[derive(Debug, PartialEq)]
+struct TableReference {
+    catalog: Option<String>,
+    database: Option<String>,
+    table: String,
+}
+
+/// Parses `table`, `database.table` or `catalog.database.table`. Parts may be
+/// quoted with double quotes or backticks to include dots, e.g. `db."weird.name"`.
+fn parse_table_reference(input: &str) -> Result<TableReference> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = input.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            // A doubled quote inside a quoted part is a literal quote
+            (Some(q), _) if c == q && chars.peek() == Some(&q) => {
+                current.push(q);
+                chars.next();
+            }
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => current.push(c),
+            (None, '"' | '`') => quote = Some(c),
+            (None, '.') => parts.push(std::mem::take(&mut current)),
+            (None, _) => current.push(c),
+        }
+    }
+    if quote.is_some() {
+        anyhow::bail!("Unterminated quote in table name: {}", input);
+    }
+    parts.push(current);
+
+    if parts.iter().any(|part| part.is_empty()) {
+        anyhow::bail!("Invalid table name: {}", input);
+    }
+
+    let mut parts = parts.into_iter();
+    match parts.len() {
+        1 => Ok(TableReference {
+            catalog: None,
+            database: None,
+            table: parts.next().unwrap_or_default(),
+        }),
+        2 => Ok(TableReference {
+            catalog: None,
+            database: parts.next(),
+            table: parts.next().unwrap_or_default(),
+        }),
+        3 => Ok(TableReference {
+            catalog: parts.next(),
+            database: parts.next(),
+            table: parts.next().unwrap_or_default(),
+        }),
+        _ => anyhow::bail!(
+            "Too many parts in table name: {} (expected 'catalog.database.table' at most)",
+            input
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(catalog: Option<&str>, database: Option<&str>, table: &str) -> TableReference {
+        TableReference {
+            catalog: catalog.map(str::to_string),
+            database: database.map(str::to_string),
+            table: table.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_partition_values() {
+        assert_eq!(
+            partition_values("dt=2024-01-01/region=us"),
+            vec![
+                ("dt".to_string(), "2024-01-01".to_string()),
+                ("region".to_string(), "us".to_string()),
+            ]
+        );
+        // Values may themselves contain '='
+        assert_eq!(
+            partition_values("expr=a=b"),
+            vec![("expr".to_string(), "a=b".to_string())]
+        );
+        assert!(partition_values("").is_empty());
+    }
+
+    #[test]
+    fn test_table_stats_from_parameters() {
+        let parameters = HashMap::from([
+            ("numRows".to_string(), "1500".to_string()),
+            ("totalSize".to_string(), "2048000".to_string()),
+            ("rawDataSize".to_string(), "-1".to_string()),
+            ("classification".to_string(), "parquet".to_string()),
+        ]);
+
+        assert_eq!(
+            TableStats::from_parameters(&parameters),
+            TableStats {
+                num_rows: Some(1500),
+                total_size: Some(2_048_000),
+                raw_data_size: None,
+            }
+        );
+        assert!(TableStats::from_parameters(&HashMap::new()).is_empty());
+    }
+
+    fn primitive(name: &str) -> ColumnType {
+        ColumnType::Primitive {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_column_type_parse() {
+        assert_eq!(
+            ColumnType::parse("decimal(10,2)"),
+            primitive("decimal(10,2)")
+        );
+        assert_eq!(
+            ColumnType::parse("array<string>"),
+            ColumnType::Array {
+                element: Box::new(primitive("string")),
+            }
+        );
+        assert_eq!(
+            ColumnType::parse("map<string,array<decimal(10,2)>>"),
+            ColumnType::Map {
+                key: Box::new(primitive("string")),
+                value: Box::new(ColumnType::Array {
+                    element: Box::new(primitive("decimal(10,2)")),
+                }),
+            }
+        );
+        assert_eq!(
+            ColumnType::parse("struct<id:bigint,tags:map<string,string>>"),
+            ColumnType::Struct {
+                fields: vec![
+                    StructField {
+                        name: "id".to_string(),
+                        field_type: primitive("bigint"),
+                    },
+                    StructField {
+                        name: "tags".to_string(),
+                        field_type: ColumnType::Map {
+                            key: Box::new(primitive("string")),
+                            value: Box::new(primitive("string")),
+                        },
+                    },
+                ],
+            }
+        );
+        // Malformed types are kept verbatim rather than dropped
+        assert_eq!(ColumnType::parse("array<string"), primitive("array<string"));
+        assert_eq!(ColumnType::parse("map<string>"), primitive("map<string>"));
+    }
+
+    #[test]
+    fn test_column_type_json() {
+        let json = serde_json::to_value(ColumnType::parse("array<struct<a:int>>")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "array",
+                "element": {
+                    "kind": "struct",
+                    "fields": [{ "name": "a", "type": { "kind": "primitive", "name": "int" } }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_table_reference() {
+        assert_eq!(
+            parse_table_reference("cat.db.tbl").unwrap(),
+            reference(Some("cat"), Some("db"), "tbl")
+        );
+        assert_eq!(
+            parse_table_reference("db.tbl").unwrap(),
+            reference(None, Some("db"), "tbl")
+        );
+        assert_eq!(
+            parse_table_reference("tbl").unwrap(),
+            reference(None, None, "tbl")
+        );
+    }
+
+    #[test]
+    fn test_parse_table_reference_quoted() {
+        assert_eq!(
+            parse_table_reference("\"weird.name\"").unwrap(),
+            reference(None, None, "weird.name")
+        );
+        assert_eq!(
+            parse_table_reference("db.`weird.name`").unwrap(),
+            reference(None, Some("db"), "weird.name")
+        );
+        assert_eq!(
+            parse_table_reference("\"my\"\"db\".tbl").unwrap(),
+            reference(None, Some("my\"db"), "tbl")
+        );
+    }
+
+    #[test]
+    fn test_parse_table_reference_invalid() {
+        assert!(parse_table_reference("db.").is_err());
+        assert!(parse_table_reference("a.b.c.d").is_err());
+        assert!(parse_table_reference("db.\"tbl").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use super::utils::DatabaseDisplay;
-use crate::cli::DatabaseArgs;
+use crate::cli::{DatabaseArgs, ListFormat};
 use crate::context::Context;
+use crate::utils::pager;
 use anyhow::Result;
 
 pub async fn list(ctx: &Context, args: &DatabaseArgs) -> Result<()> {
@@ -22,20 +23,25 @@ pub async fn list(ctx: &Context, args: &DatabaseArgs) -> Result<()> {
 
     let databases = result.database_list();
 
+    if args.format == ListFormat::Json {
+        println!("{}", DatabaseDisplay::create_databases_json(databases)?);
+        return Ok(());
+    }
+
     if databases.is_empty() {
-        println!("No databases found in catalog: {}", ctx.catalog());
+        eprintln!("No databases found in catalog: {}", ctx.catalog());
         return Ok(());
     }
 
     // Display databases in a simple list
-    println!(
+    eprintln!(
         "Databases in catalog: {} (workgroup: {})",
         ctx.catalog(),
         workgroup
     );
 
     let table = DatabaseDisplay::create_databases_table(databases);
-    table.printstd();
+    pager::page_table(&table)?;
 
     Ok(())
 }
@@ -5,4 +5,4 @@ pub mod utils;
 
 pub use describe::describe_table;
 pub use list::list;
-pub use tables::list_tables;
+pub use tables::{is_table_not_found, list_tables, table_names};
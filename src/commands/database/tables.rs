@@ -1,8 +1,11 @@
 use super::utils::TableMetadataDisplay;
-use crate::cli::TableArgs;
+use crate::cli::{ListFormat, TableArgs};
 use crate::context::Context;
-use crate::utils::filter;
+use crate::utils::{filter, pager};
 use anyhow::{Context as _, Result};
+use aws_sdk_athena::error::SdkError;
+use aws_sdk_athena::operation::get_table_metadata::GetTableMetadataError;
+use aws_sdk_athena::Client;
 
 pub async fn list_tables(ctx: &Context, args: &TableArgs) -> Result<()> {
     let client = ctx.create_athena_client();
@@ -30,39 +33,57 @@ pub async fn list_tables(ctx: &Context, args: &TableArgs) -> Result<()> {
 
     let tables = result.table_metadata_list();
 
-    // Debug: Print all tables from server
-    println!("DEBUG: Received {} tables from server", tables.len());
-    if !tables.is_empty() {
-        println!("DEBUG: First few table names:");
-        for (i, table) in tables.iter().take(5).enumerate() {
-            println!("  {}. {}", i + 1, table.name());
+    if ctx.debug() {
+        eprintln!("DEBUG: Received {} tables from server", tables.len());
+        if !tables.is_empty() {
+            eprintln!("DEBUG: First few table names:");
+            for (i, table) in tables.iter().take(5).enumerate() {
+                eprintln!("  {}. {}", i + 1, table.name());
+            }
         }
     }
 
-    if tables.is_empty() {
-        println!("No tables found in database: {}", database);
+    let json = args.format == ListFormat::Json;
+
+    if tables.is_empty() && !json {
+        eprintln!("No tables found in database: {}", database);
         return Ok(());
     }
 
     // Apply filter if specified
     let filtered_tables = if let Some(filter_pattern) = &args.filter {
-        println!("DEBUG: Applying filter pattern: '{}'", filter_pattern);
+        if ctx.debug() {
+            eprintln!("DEBUG: Applying filter pattern: '{}'", filter_pattern);
+        }
 
         // Use filter_items from the utils module
-        let filtered = filter::filter_items(tables, Some(filter_pattern), |table| table.name());
-
-        println!(
-            "DEBUG: Filter reduced tables from {} to {}",
-            tables.len(),
-            filtered.len()
-        );
+        let filtered =
+            filter::filter_items(tables, Some(filter_pattern), args.case_sensitive, |table| {
+                table.name()
+            });
+
+        if ctx.debug() {
+            eprintln!(
+                "DEBUG: Filter reduced tables from {} to {}",
+                tables.len(),
+                filtered.len()
+            );
+        }
         filtered
     } else {
         tables.iter().collect()
     };
 
-    if filtered_tables.is_empty() {
+    if json {
         println!(
+            "{}",
+            TableMetadataDisplay::create_table_metadata_json(&filtered_tables)?
+        );
+        return Ok(());
+    }
+
+    if filtered_tables.is_empty() {
+        eprintln!(
             "No tables found matching filter: {}",
             args.filter.as_ref().unwrap()
         );
@@ -70,7 +91,7 @@ pub async fn list_tables(ctx: &Context, args: &TableArgs) -> Result<()> {
     }
 
     // Display tables
-    println!(
+    eprintln!(
         "Tables in database: {} (filtered: {})",
         database,
         args.filter.as_deref().unwrap_or("none")
@@ -78,7 +99,46 @@ pub async fn list_tables(ctx: &Context, args: &TableArgs) -> Result<()> {
 
     // Create a pretty table using our display struct
     let table = TableMetadataDisplay::create_table_metadata_table(&filtered_tables);
-    table.printstd();
+    pager::page_table(&table)?;
 
     Ok(())
 }
+
+/// Names of all tables in a database, across every page of results
+pub async fn table_names(client: &Client, catalog: &str, database: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let result = client
+            .list_table_metadata()
+            .catalog_name(catalog)
+            .database_name(database)
+            .set_next_token(next_token)
+            .send()
+            .await
+            .context("Failed to list tables")?;
+
+        names.extend(
+            result
+                .table_metadata_list()
+                .iter()
+                .map(|table| table.name().to_string()),
+        );
+
+        next_token = result.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(names)
+}
+
+/// Whether a `get_table_metadata` call failed because the table doesn't exist
+pub fn is_table_not_found(err: &SdkError<GetTableMetadataError>) -> bool {
+    matches!(
+        err.as_service_error(),
+        Some(GetTableMetadataError::MetadataException(_))
+    )
+}
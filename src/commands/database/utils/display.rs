@@ -1,5 +1,6 @@
 use aws_sdk_athena::types::Column;
 use prettytable::Cell;
+use serde::Serialize;
 
 /// Display struct for AWS Athena Column
 pub struct ColumnDisplay {
@@ -28,30 +29,87 @@ impl From<Column> for ColumnDisplay {
 }
 
 impl ColumnDisplay {
-    /// Convert the ColumnDisplay into a prettytable Row
-    pub fn to_row(&self) -> prettytable::Row {
-        prettytable::Row::new(vec![
-            Cell::new(&self.name),
-            Cell::new(&self.column_type),
-            Cell::new(&self.comment),
-        ])
+    /// Convert the ColumnDisplay into a prettytable Row, with the simplified
+    /// type after the raw one when `simple_type` is set
+    pub fn to_row(&self, simple_type: bool) -> prettytable::Row {
+        let mut cells = vec![Cell::new(&self.name), Cell::new(&self.column_type)];
+        if simple_type {
+            cells.push(Cell::new(&normalize_type(&self.column_type)));
+        }
+        cells.push(Cell::new(&self.comment));
+        prettytable::Row::new(cells)
     }
 
-    /// Create a formatted table from a slice of Columns
-    pub fn create_columns_table(columns: &[Column]) -> prettytable::Table {
+    /// Create a formatted table from a slice of Columns, optionally with a
+    /// "Simple Type" column (see [`normalize_type`])
+    pub fn create_columns_table(columns: &[Column], simple_types: bool) -> prettytable::Table {
         let mut table = prettytable::Table::new();
 
         // Add header row with styling
-        table.add_row(header_row(&["Name", "Type", "Description"]));
+        if simple_types {
+            table.add_row(header_row(&["Name", "Type", "Simple Type", "Description"]));
+        } else {
+            table.add_row(header_row(&["Name", "Type", "Description"]));
+        }
 
         // Add data rows
         for column in columns {
             let display = ColumnDisplay::from(column);
-            table.add_row(display.to_row());
+            table.add_row(display.to_row(simple_types));
         }
 
         table
     }
+
+    /// Create one table of the regular columns followed by the partition keys,
+    /// with a "Partition" column telling them apart
+    pub fn create_merged_columns_table(
+        columns: &[Column],
+        partition_keys: &[Column],
+        simple_types: bool,
+    ) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        let mut headers = vec!["Name", "Type"];
+        if simple_types {
+            headers.push("Simple Type");
+        }
+        headers.extend(["Partition", "Description"]);
+        table.add_row(header_row(&headers));
+
+        // The partition flag goes just before the description
+        let partition_index = headers.len() - 2;
+        let flagged = columns
+            .iter()
+            .map(|column| (column, false))
+            .chain(partition_keys.iter().map(|key| (key, true)));
+        for (column, is_partition) in flagged {
+            let mut row = ColumnDisplay::from(column).to_row(simple_types);
+            row.insert_cell(partition_index, Cell::new(&is_partition.to_string()));
+            table.add_row(row);
+        }
+
+        table
+    }
+}
+
+/// Simplifies a column type to its family, dropping parameters and nested
+/// types: `varchar(255)` is `string`, `decimal(10,2)` is `decimal` and
+/// `array<struct<...>>` is `array`
+pub fn normalize_type(column_type: &str) -> String {
+    let base = column_type
+        .split(['(', '<'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    match base.as_str() {
+        "varchar" | "char" | "string" => "string",
+        "int" | "integer" => "int",
+        _ => base.as_str(),
+    }
+    .to_string()
 }
 
 /// Helper struct for parameter display
@@ -97,6 +155,7 @@ impl ParameterDisplay {
 }
 
 /// Display struct for database information
+#[derive(Serialize)]
 pub struct DatabaseDisplay {
     /// Database name
     name: String,
@@ -135,9 +194,18 @@ impl DatabaseDisplay {
 
         table
     }
+
+    /// Serialize a slice of Databases as a pretty-printed JSON array
+    pub fn create_databases_json(
+        databases: &[aws_sdk_athena::types::Database],
+    ) -> serde_json::Result<String> {
+        let displays: Vec<Self> = databases.iter().map(Self::from_database).collect();
+        serde_json::to_string_pretty(&displays)
+    }
 }
 
 /// Display struct for table metadata
+#[derive(Serialize)]
 pub struct TableMetadataDisplay {
     /// Table name
     name: String,
@@ -187,6 +255,17 @@ impl TableMetadataDisplay {
 
         table
     }
+
+    /// Serialize a slice of TableMetadata as a pretty-printed JSON array
+    pub fn create_table_metadata_json(
+        tables: &[&aws_sdk_athena::types::TableMetadata],
+    ) -> serde_json::Result<String> {
+        let displays: Vec<Self> = tables
+            .iter()
+            .map(|table| Self::from_table_metadata(table))
+            .collect();
+        serde_json::to_string_pretty(&displays)
+    }
 }
 
 /// Helper function to create a styled header cell
@@ -199,3 +278,59 @@ pub fn header_row(headers: &[&str]) -> prettytable::Row {
     let cells = headers.iter().map(|&text| header_cell(text)).collect();
     prettytable::Row::new(cells)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, column_type: &str) -> Column {
+        Column::builder()
+            .name(name)
+            .r#type(column_type)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merged_columns_table() {
+        let columns = [column("id", "bigint"), column("name", "varchar(20)")];
+        let partition_keys = [column("dt", "string")];
+
+        let table = ColumnDisplay::create_merged_columns_table(&columns, &partition_keys, true);
+        let rows: Vec<Vec<String>> = table
+            .row_iter()
+            .map(|row| row.iter().map(|cell| cell.get_content()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Name", "Type", "Simple Type", "Partition", "Description"],
+                vec!["id", "bigint", "bigint", "false", ""],
+                vec!["name", "varchar(20)", "string", "false", ""],
+                vec!["dt", "string", "string", "true", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_type() {
+        assert_eq!(normalize_type("varchar(255)"), "string");
+        assert_eq!(normalize_type("char(2)"), "string");
+        assert_eq!(normalize_type("string"), "string");
+        assert_eq!(normalize_type("decimal(10,2)"), "decimal");
+        assert_eq!(normalize_type("timestamp(3)"), "timestamp");
+        assert_eq!(normalize_type("integer"), "int");
+        assert_eq!(normalize_type("BIGINT"), "bigint");
+    }
+
+    #[test]
+    fn test_normalize_nested_type() {
+        assert_eq!(
+            normalize_type("array<struct<id:int,name:varchar(10)>>"),
+            "array"
+        );
+        assert_eq!(normalize_type("map<string,array<int>>"), "map");
+        assert_eq!(normalize_type("struct<a:decimal(10,2)>"), "struct");
+        assert_eq!(normalize_type(""), "");
+    }
+}
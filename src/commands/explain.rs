@@ -0,0 +1,64 @@
+//! Shows Athena's plan for a query without running it.
+
+use crate::cli::{ExplainArgs, ExplainFormat};
+use crate::commands::query;
+use crate::context::Context;
+use crate::validation;
+use anyhow::Result;
+use std::time::Duration;
+
+/// Runs `EXPLAIN` for the query and prints the plan text as Athena returns it
+pub async fn explain(ctx: &Context, args: &ExplainArgs) -> Result<()> {
+    // sqlparser doesn't know Athena's EXPLAIN options, so only the inner query is checked
+    validation::validate_query_syntax(&args.query)?;
+
+    let database = ctx
+        .database()
+        .ok_or_else(|| anyhow::anyhow!("Database name is required but was not provided"))?;
+    let client = ctx.create_athena_client();
+
+    // Plans are cheap to compute, so always ask for a fresh one
+    let query_id = query::start_query(
+        &client,
+        &database,
+        &explain_statement(&args.query, args.format),
+        &ctx.workgroup(),
+        Duration::ZERO,
+        ctx.output_location().as_deref(),
+    )
+    .await?;
+    query::wait_for_query(&client, &query_id).await?;
+
+    for line in query::first_column_values(&client, &query_id).await? {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Wraps a query in `EXPLAIN` with the requested output format
+fn explain_statement(query: &str, format: ExplainFormat) -> String {
+    let format = match format {
+        ExplainFormat::Text => "TEXT",
+        ExplainFormat::Json => "JSON",
+        ExplainFormat::Graphviz => "GRAPHVIZ",
+    };
+    format!("EXPLAIN (FORMAT {}) {}", format, query.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_statement() {
+        assert_eq!(
+            explain_statement("  SELECT * FROM orders\n", ExplainFormat::Text),
+            "EXPLAIN (FORMAT TEXT) SELECT * FROM orders"
+        );
+        assert_eq!(
+            explain_statement("SELECT 1", ExplainFormat::Graphviz),
+            "EXPLAIN (FORMAT GRAPHVIZ) SELECT 1"
+        );
+    }
+}
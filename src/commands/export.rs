@@ -0,0 +1,319 @@
+//! Saving query results to local files in the formats supported by `query --format`.
+
+use crate::cli::{CsvQuoteStyle, ResultFormat};
+use crate::commands::inspect::download::is_file_destination;
+use crate::config::OutputFormat;
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+impl ResultFormat {
+    /// File extension used for files written in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrow",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Format matching a path's file extension, if it is a supported one
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        [
+            Self::Csv,
+            Self::Json,
+            Self::Parquet,
+            Self::Arrow,
+            Self::Ndjson,
+        ]
+        .into_iter()
+        .find(|format| format.extension() == extension)
+    }
+
+    /// The result format for a configured output format, `None` for a table
+    pub fn from_output_format(format: OutputFormat) -> Option<Self> {
+        match format {
+            OutputFormat::Table => None,
+            OutputFormat::Csv => Some(Self::Csv),
+            OutputFormat::Json => Some(Self::Json),
+            OutputFormat::Ndjson => Some(Self::Ndjson),
+            OutputFormat::Parquet => Some(Self::Parquet),
+            OutputFormat::Arrow => Some(Self::Arrow),
+        }
+    }
+
+    /// Whether results in this format can only be written to a file
+    pub fn needs_file(self) -> bool {
+        matches!(self, Self::Csv | Self::Parquet | Self::Arrow)
+    }
+}
+
+impl From<ResultFormat> for OutputFormat {
+    fn from(format: ResultFormat) -> Self {
+        match format {
+            ResultFormat::Csv => Self::Csv,
+            ResultFormat::Json => Self::Json,
+            ResultFormat::Ndjson => Self::Ndjson,
+            ResultFormat::Parquet => Self::Parquet,
+            ResultFormat::Arrow => Self::Arrow,
+        }
+    }
+}
+
+/// Whether `output` names a file whose extension is a supported format
+pub fn has_format_extension(output: &str) -> bool {
+    is_file_destination(output) && ResultFormat::from_path(Path::new(output)).is_some()
+}
+
+/// How CSV files are written. The defaults match Polars': comma-separated,
+/// quoted where necessary and without a byte order mark
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub bom: bool,
+    pub quote_style: CsvQuoteStyle,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            bom: false,
+            quote_style: CsvQuoteStyle::Necessary,
+        }
+    }
+}
+
+/// Parses a CSV delimiter: a single ASCII character, with "\t" for a tab
+pub fn parse_delimiter(arg: &str) -> Result<u8, String> {
+    let delimiter = match arg {
+        "\\t" | "tab" => '\t',
+        _ => {
+            let mut chars = arg.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("expected a single character, got '{}'", arg)),
+            }
+        }
+    };
+
+    if !delimiter.is_ascii() || matches!(delimiter, '"' | '\n' | '\r') {
+        return Err(format!(
+            "'{}' can't be used as a delimiter; use an ASCII character other than a quote or line break",
+            arg
+        ));
+    }
+    Ok(delimiter as u8)
+}
+
+/// Computes where results are saved: `output` itself when it names a file, or a
+/// file named after the query execution ID inside the `output` directory
+pub fn output_file_path(output: &str, query_id: &str, format: ResultFormat) -> PathBuf {
+    if is_file_destination(output) {
+        PathBuf::from(output)
+    } else {
+        Path::new(output).join(format!("{}.{}", query_id, format.extension()))
+    }
+}
+
+/// Writes `df` to `output` (a file or directory, see [`output_file_path`]) and
+/// returns the path of the written file. Without an explicit `format`, it is
+/// taken from the file extension, falling back to CSV.
+pub fn save_dataframe(
+    df: &mut DataFrame,
+    output: &str,
+    query_id: &str,
+    format: Option<ResultFormat>,
+    csv: &CsvOptions,
+) -> Result<PathBuf> {
+    let format = format
+        .or_else(|| {
+            is_file_destination(output)
+                .then(|| ResultFormat::from_path(Path::new(output)))
+                .flatten()
+        })
+        .unwrap_or(ResultFormat::Csv);
+    let path = output_file_path(output, query_id, format);
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context(format!(
+            "Failed to create output directory: {}",
+            parent.display()
+        ))?;
+    }
+
+    let file =
+        File::create(&path).context(format!("Failed to create output file: {}", path.display()))?;
+    write_dataframe(df, format, csv, BufWriter::new(file))
+        .context(format!("Failed to write results to: {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Serializes `df` in the given format, using `csv` for CSV
+pub fn write_dataframe<W: Write>(
+    df: &mut DataFrame,
+    format: ResultFormat,
+    csv: &CsvOptions,
+    mut writer: W,
+) -> Result<()> {
+    match format {
+        ResultFormat::Csv => CsvWriter::new(&mut writer)
+            .with_separator(csv.delimiter)
+            .include_bom(csv.bom)
+            .with_quote_style(match csv.quote_style {
+                CsvQuoteStyle::Necessary => QuoteStyle::Necessary,
+                CsvQuoteStyle::Always => QuoteStyle::Always,
+                CsvQuoteStyle::NonNumeric => QuoteStyle::NonNumeric,
+                CsvQuoteStyle::Never => QuoteStyle::Never,
+            })
+            .finish(df)?,
+        ResultFormat::Json => JsonWriter::new(&mut writer)
+            .with_json_format(JsonFormat::Json)
+            .finish(df)?,
+        ResultFormat::Parquet => {
+            ParquetWriter::new(&mut writer).finish(df)?;
+        }
+        ResultFormat::Arrow => IpcWriter::new(&mut writer).finish(df)?,
+        ResultFormat::Ndjson => JsonWriter::new(&mut writer)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(df)?,
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_file_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+
+        // Directories get a file named after the query
+        assert_eq!(
+            output_file_path(dir_str, "abc-123", ResultFormat::Parquet),
+            dir.path().join("abc-123.parquet")
+        );
+        assert_eq!(
+            output_file_path("exports/", "abc-123", ResultFormat::Csv),
+            Path::new("exports/").join("abc-123.csv")
+        );
+
+        // File paths are used as given
+        assert_eq!(
+            output_file_path("exports/daily.json", "abc-123", ResultFormat::Json),
+            PathBuf::from("exports/daily.json")
+        );
+    }
+
+    #[test]
+    fn test_save_dataframe_infers_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut df = df!("id" => ["1", "2"], "name" => ["a", "b"]).unwrap();
+
+        let csv = dir.path().join("results.csv");
+        save_dataframe(
+            &mut df,
+            csv.to_str().unwrap(),
+            "abc-123",
+            None,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(&csv).unwrap(), "id,name\n1,a\n2,b\n");
+
+        // An explicit format wins, and directories fall back to the query ID
+        let path = save_dataframe(
+            &mut df,
+            dir.path().to_str().unwrap(),
+            "abc-123",
+            Some(ResultFormat::Parquet),
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(path, dir.path().join("abc-123.parquet"));
+        let read = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        assert!(read.equals(&df));
+    }
+
+    #[test]
+    fn test_save_dataframe_csv_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut df = df!("id" => [1i64, 2], "name" => ["a;b", "c"]).unwrap();
+
+        let path = dir.path().join("excel.csv");
+        let options = CsvOptions {
+            delimiter: b';',
+            bom: true,
+            quote_style: CsvQuoteStyle::Necessary,
+        };
+        save_dataframe(&mut df, path.to_str().unwrap(), "abc-123", None, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{feff}id;name\n1;\"a;b\"\n2;c\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(";"), Ok(b';'));
+        assert_eq!(parse_delimiter("|"), Ok(b'|'));
+        assert_eq!(parse_delimiter("\\t"), Ok(b'\t'));
+        assert_eq!(parse_delimiter("\t"), Ok(b'\t'));
+        assert!(parse_delimiter(";;").is_err());
+        assert!(parse_delimiter("").is_err());
+        assert!(parse_delimiter("é").is_err());
+        assert!(parse_delimiter("\"").is_err());
+    }
+
+    #[test]
+    fn test_save_dataframe_ndjson() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut df = df!("id" => [1i64, 2], "name" => [Some("a"), None]).unwrap();
+
+        let path = dir.path().join("results.ndjson");
+        save_dataframe(
+            &mut df,
+            path.to_str().unwrap(),
+            "abc-123",
+            None,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_save_dataframe_arrow_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut df = df!("id" => [1i64, 2], "name" => ["a", "b"]).unwrap();
+
+        let path = dir.path().join("results.arrow");
+        save_dataframe(
+            &mut df,
+            path.to_str().unwrap(),
+            "abc-123",
+            None,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+
+        let read = IpcReader::new(File::open(&path).unwrap()).finish().unwrap();
+        assert!(read.equals(&df));
+        assert_eq!(read.dtypes(), df.dtypes());
+    }
+}
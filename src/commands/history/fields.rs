@@ -1,4 +1,7 @@
-use crate::commands::common::{OptionByteDisplay, OptionDisplayValue, OptionDurationFormat};
+use crate::commands::common::{
+    engine_version, execution_cost, format_cost, used_result_cache, OptionByteDisplay,
+    OptionDateTimeFormat, OptionDisplayValue, OptionDurationFormat, TimestampFormat,
+};
 use crate::config;
 use aws_sdk_athena::types::QueryExecution;
 use std::fmt;
@@ -13,10 +16,12 @@ pub enum HistoryField {
     StartTime,
     EndTime,
     DataScanned,
+    EstimatedCost,
     Runtime,
     OutputLocation,
     Cache,
     RowCount,
+    EngineVersion,
 }
 
 // Add FromStr implementation for parsing from config
@@ -31,10 +36,12 @@ impl FromStr for HistoryField {
             "StartTime" => Ok(HistoryField::StartTime),
             "EndTime" => Ok(HistoryField::EndTime),
             "DataScanned" => Ok(HistoryField::DataScanned),
+            "EstimatedCost" => Ok(HistoryField::EstimatedCost),
             "Runtime" => Ok(HistoryField::Runtime),
             "OutputLocation" => Ok(HistoryField::OutputLocation),
             "Cache" => Ok(HistoryField::Cache),
             "RowCount" => Ok(HistoryField::RowCount),
+            "EngineVersion" => Ok(HistoryField::EngineVersion),
             _ => Err(format!("Unknown history field: {}", s)),
         }
     }
@@ -49,10 +56,12 @@ impl fmt::Display for HistoryField {
             HistoryField::StartTime => write!(f, "Start Time"),
             HistoryField::EndTime => write!(f, "End Time"),
             HistoryField::DataScanned => write!(f, "Data Scanned"),
+            HistoryField::EstimatedCost => write!(f, "Estimated Cost"),
             HistoryField::Runtime => write!(f, "Runtime"),
             HistoryField::OutputLocation => write!(f, "Output Location"),
             HistoryField::Cache => write!(f, "Cache"),
             HistoryField::RowCount => write!(f, "Row Count"),
+            HistoryField::EngineVersion => write!(f, "Engine Version"),
         }
     }
 }
@@ -66,6 +75,7 @@ pub fn default_history_fields() -> Vec<HistoryField> {
         HistoryField::StartTime,
         HistoryField::EndTime,
         HistoryField::DataScanned,
+        HistoryField::EstimatedCost,
         HistoryField::Runtime,
         HistoryField::RowCount,
         HistoryField::OutputLocation,
@@ -75,7 +85,7 @@ pub fn default_history_fields() -> Vec<HistoryField> {
 
 // Get fields from config or use defaults
 pub fn get_history_fields() -> Vec<HistoryField> {
-    if let Ok(config) = config::Config::load() {
+    if let Ok(config) = config::Config::load(false) {
         if let Some(field_names) = config.app.history_fields {
             let fields: Vec<HistoryField> = field_names
                 .iter()
@@ -101,38 +111,38 @@ pub fn format_status(status: &Option<&aws_sdk_athena::types::QueryExecutionStatu
 }
 
 // Extract a field value from a query execution
-pub fn get_field_value(execution: &QueryExecution, field: HistoryField) -> String {
+pub fn get_field_value(
+    execution: &QueryExecution,
+    field: HistoryField,
+    timestamps: &TimestampFormat,
+    price_per_tb: f64,
+) -> String {
     match field {
         HistoryField::Id => execution.query_execution_id().to_display_value_or_default(),
 
         HistoryField::Status => format_status(&execution.status()),
 
-        HistoryField::Query => execution
-            .query()
-            .map(|q| {
-                if q.len() > 30 {
-                    format!("{}...", &q[..27])
-                } else {
-                    q.to_string()
-                }
-            })
-            .to_display_value_or_default(),
+        HistoryField::Query => execution.query().to_display_value_or_default(),
 
         HistoryField::StartTime => execution
             .status()
             .and_then(|s| s.submission_date_time())
-            .to_display_value_or_default(),
+            .format_datetime_or_default(timestamps),
 
         HistoryField::EndTime => execution
             .status()
             .and_then(|s| s.completion_date_time())
-            .to_display_value_or_default(),
+            .format_datetime_or_default(timestamps),
 
         HistoryField::DataScanned => execution
             .statistics()
             .and_then(|s| s.data_scanned_in_bytes())
             .format_bytes_or_default(),
 
+        HistoryField::EstimatedCost => execution_cost(execution, price_per_tb)
+            .map(format_cost)
+            .to_display_value_or_default(),
+
         HistoryField::Runtime => execution
             .statistics()
             .and_then(|s| s.engine_execution_time_in_millis())
@@ -144,12 +154,7 @@ pub fn get_field_value(execution: &QueryExecution, field: HistoryField) -> Strin
             .to_display_value_or_default(),
 
         HistoryField::Cache => {
-            let data_scanned = execution
-                .statistics()
-                .and_then(|s| s.data_scanned_in_bytes())
-                .unwrap_or(1);
-
-            if data_scanned == 0 {
+            if used_result_cache(execution) {
                 "Used cache".to_string()
             } else {
                 "-".to_string()
@@ -162,5 +167,7 @@ pub fn get_field_value(execution: &QueryExecution, field: HistoryField) -> Strin
             // For now, return a placeholder
             "-".to_string()
         }
+
+        HistoryField::EngineVersion => engine_version(execution).to_display_value_or_default(),
     }
 }
@@ -1,11 +1,74 @@
-use super::fields::{get_field_value, HistoryField};
+use super::fields::{format_status, get_field_value, HistoryField};
+use super::summary::HistorySummary;
 use crate::cli::HistoryArgs;
+use crate::commands::common::{table_cell, ByteDisplay};
+use crate::config::OutputFormat;
 use crate::context::Context;
+use crate::error::AthenaCliError;
+use crate::utils::pager;
 use anyhow::Result;
+use aws_sdk_athena::types::QueryExecution;
+use chrono::Local;
+use futures::stream::{self, StreamExt};
 use prettytable::{Cell, Row};
 use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Maximum number of concurrent `get_query_runtime_statistics` calls
+const ROW_COUNT_CONCURRENCY: usize = 8;
 
 pub async fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
+    if args.local {
+        return super::local::list(ctx, args);
+    }
+    if let Some(interval) = args.watch {
+        return watch(ctx, args, interval).await;
+    }
+
+    show(ctx, args).await
+}
+
+/// Clears the screen and shows the history again every `interval`, like `top`,
+/// until interrupted with Ctrl-C
+async fn watch(ctx: &Context, args: &HistoryArgs, interval: Duration) -> Result<()> {
+    if interval.is_zero() {
+        return Err(AthenaCliError::Validation(
+            "--watch interval must be greater than zero".to_string(),
+        )
+        .into());
+    }
+
+    // The pager would wait for a keypress and hold up the next refresh
+    pager::configure(true);
+
+    let term = console::Term::stdout();
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let refresh = async {
+            ticks.tick().await;
+            term.clear_screen()?;
+            println!(
+                "Every {} in workgroup {}, last refreshed {}",
+                humantime::format_duration(interval),
+                ctx.workgroup(),
+                Local::now().format("%H:%M:%S")
+            );
+            show(ctx, args).await
+        };
+
+        tokio::select! {
+            result = refresh => result?,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// Fetches the workgroup's recent executions and shows them as selected by `args`
+async fn show(ctx: &Context, args: &HistoryArgs) -> Result<()> {
     let client = ctx.create_athena_client();
     let workgroup = ctx.workgroup();
 
@@ -22,11 +85,11 @@ pub async fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
     // Get query IDs
     let query_ids = result.query_execution_ids();
     if query_ids.is_empty() {
-        println!("No queries found in workgroup: {}", workgroup);
+        eprintln!("No queries found in workgroup: {}", workgroup);
         return Ok(());
     }
 
-    println!(
+    eprintln!(
         "Found {} queries in workgroup: {}",
         query_ids.len(),
         workgroup
@@ -40,14 +103,46 @@ pub async fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
         .await?;
 
     // Create a map of query ID to execution for quick lookup
-    let executions_map: HashMap<String, &aws_sdk_athena::types::QueryExecution> = details
+    let executions_map: HashMap<String, &QueryExecution> = details
         .query_executions()
         .iter()
         .filter_map(|exec| exec.query_execution_id().map(|id| (id.to_string(), exec)))
         .collect();
 
+    if args.summary {
+        let mut summary = HistorySummary::default();
+        for execution in query_ids
+            .iter()
+            .filter_map(|id| executions_map.get(id))
+            .filter(|execution| matches_filters(execution, args))
+        {
+            summary.add(
+                &format_status(&execution.status()),
+                execution
+                    .statistics()
+                    .and_then(|s| s.data_scanned_in_bytes()),
+            );
+        }
+        summary.print();
+        return Ok(());
+    }
+
+    if args.group_by_query {
+        print_query_groups(
+            query_ids
+                .iter()
+                .filter_map(|id| executions_map.get(id).copied())
+                .filter(|execution| matches_filters(execution, args)),
+            ctx.max_column_width(),
+        )?;
+        return Ok(());
+    }
+
     // Only fetch row counts if the RowCount field is being displayed
     let fields = super::fields::get_history_fields();
+    let timestamps = ctx.timestamp_format();
+    let price_per_tb = ctx.price_per_tb();
+    let max_width = ctx.max_column_width();
     let mut row_counts: HashMap<String, String> = HashMap::new();
 
     if fields.contains(&HistoryField::RowCount) {
@@ -65,56 +160,45 @@ pub async fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
             .map(|id| id.to_string())
             .collect();
 
-        // Fetch row counts for successful queries in batches to reduce API calls
-        for chunk in succeeded_query_ids.chunks(10) {
-            for query_id in chunk {
-                match client
-                    .get_query_runtime_statistics()
-                    .query_execution_id(query_id)
-                    .send()
-                    .await
-                {
-                    Ok(stats) => {
-                        if let Some(rows) = stats.query_runtime_statistics().and_then(|s| s.rows())
-                        {
-                            if let Some(output_rows) = rows.output_rows() {
-                                row_counts.insert(query_id.clone(), output_rows.to_string());
-                            }
+        // Fetch row counts for successful queries concurrently, with a bounded limit
+        row_counts = stream::iter(succeeded_query_ids)
+            .map(|query_id| {
+                let client = &client;
+                async move {
+                    match client
+                        .get_query_runtime_statistics()
+                        .query_execution_id(&query_id)
+                        .send()
+                        .await
+                    {
+                        Ok(stats) => stats
+                            .query_runtime_statistics()
+                            .and_then(|s| s.rows())
+                            .and_then(|rows| rows.output_rows())
+                            .map(|output_rows| (query_id, output_rows.to_string())),
+                        Err(e) => {
+                            // Log the error but continue processing
+                            eprintln!("Failed to get row count for query {}: {}", query_id, e);
+                            None
                         }
                     }
-                    Err(e) => {
-                        // Log the error but continue processing
-                        eprintln!("Failed to get row count for query {}: {}", query_id, e);
-                    }
                 }
-            }
-        }
+            })
+            .buffer_unordered(ROW_COUNT_CONCURRENCY)
+            .filter_map(|count| async move { count })
+            .collect()
+            .await;
     }
 
-    // Process query IDs in the original order
-    // Create a new table
-    let mut table = prettytable::Table::new();
-
-    // Add header row with styling
-    let header_cells = fields
-        .iter()
-        .map(|field| prettytable::Cell::new(&field.to_string()).style_spec("Fb"))
-        .collect();
-    table.add_row(prettytable::Row::new(header_cells));
-
-    // Add data rows in the original order from query_ids
+    // Collect field values in the original order from query_ids
+    let mut rows: Vec<Vec<String>> = Vec::new();
     for query_id in query_ids {
         if let Some(execution) = executions_map.get(query_id) {
             // Filter by status if specified
-            if let Some(status_filter) = &args.status {
-                if let Some(status) = execution.status().and_then(|s| s.state()) {
-                    if status.as_str() != status_filter.to_uppercase() {
-                        continue;
-                    }
-                }
+            if !matches_filters(execution, args) {
+                continue;
             }
 
-            // Collect field values
             let row_values: Vec<String> = fields
                 .iter()
                 .map(|&field| {
@@ -128,17 +212,121 @@ pub async fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
                             "-".to_string()
                         }
                     } else {
-                        get_field_value(execution, field)
+                        get_field_value(execution, field, &timestamps, price_per_tb)
                     }
                 })
                 .collect();
-
-            // Create cells for the row
-            let cells: Vec<Cell> = row_values.iter().map(|val| Cell::new(val)).collect();
-            table.add_row(Row::new(cells));
+            rows.push(row_values);
         }
     }
 
-    table.printstd();
+    if ctx.history_format(args.format.map(Into::into)) == OutputFormat::Json {
+        // One object per query, keyed by the field names used in history_fields
+        let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+            .into_iter()
+            .map(|values| {
+                fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| (format!("{:?}", field), value.into()))
+                    .collect()
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&objects)?);
+        return Ok(());
+    }
+
+    let mut table = prettytable::Table::new();
+
+    // Add header row with styling
+    let header_cells = fields
+        .iter()
+        .map(|field| prettytable::Cell::new(&field.to_string()).style_spec("Fb"))
+        .collect();
+    table.add_row(prettytable::Row::new(header_cells));
+
+    for row_values in &rows {
+        let cells: Vec<Cell> = row_values
+            .iter()
+            .map(|val| table_cell(val, max_width))
+            .collect();
+        table.add_row(Row::new(cells));
+    }
+
+    pager::page_table(&table)?;
     Ok(())
 }
+
+/// Whether an execution passes the `--status` and `--grep` filters
+fn matches_filters(execution: &QueryExecution, args: &HistoryArgs) -> bool {
+    let status_matches = match (args.status, execution.status().and_then(|s| s.state())) {
+        (Some(filter), Some(status)) => status.as_str() == filter.as_str(),
+        _ => true,
+    };
+    status_matches && matches_grep(execution.query().unwrap_or_default(), args.grep.as_deref())
+}
+
+/// Whether a query's text contains the `--grep` text, ignoring case
+pub(super) fn matches_grep(query: &str, grep: Option<&str>) -> bool {
+    grep.is_none_or(|text| query.to_lowercase().contains(&text.to_lowercase()))
+}
+
+/// Prints one row per distinct query (after normalizing whitespace) with its run
+/// count and total data scanned, most expensive first
+fn print_query_groups<'a>(
+    executions: impl Iterator<Item = &'a QueryExecution>,
+    max_width: usize,
+) -> io::Result<()> {
+    // Normalized query -> (runs, total bytes scanned)
+    let mut groups: HashMap<String, (usize, i64)> = HashMap::new();
+    for execution in executions {
+        let query = normalize_query(execution.query().unwrap_or_default());
+        let group = groups.entry(query).or_default();
+        group.0 += 1;
+        group.1 += execution
+            .statistics()
+            .and_then(|s| s.data_scanned_in_bytes())
+            .unwrap_or(0);
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|(_, (runs_a, bytes_a)), (_, (runs_b, bytes_b))| {
+        bytes_b.cmp(bytes_a).then(runs_b.cmp(runs_a))
+    });
+
+    let mut table = prettytable::Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Query").style_spec("Fb"),
+        Cell::new("Runs").style_spec("Fb"),
+        Cell::new("Total Data Scanned").style_spec("Fb"),
+    ]));
+    for (query, (runs, bytes)) in groups {
+        table.add_row(Row::new(vec![
+            table_cell(&query, max_width),
+            Cell::new(&runs.to_string()),
+            Cell::new(&bytes.format_bytes()),
+        ]));
+    }
+
+    pager::page_table(&table)
+}
+
+/// Trims query text and collapses runs of whitespace so reformatted copies of
+/// the same SQL are grouped together
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("  SELECT *\n  FROM   orders\tWHERE id = 1 "),
+            "SELECT * FROM orders WHERE id = 1"
+        );
+        assert_eq!(normalize_query(""), "");
+    }
+}
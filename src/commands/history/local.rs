@@ -0,0 +1,79 @@
+use super::summary::HistorySummary;
+use crate::cli::HistoryArgs;
+use crate::commands::common::{table_cell, DateTimeFormat, OptionByteDisplay};
+use crate::config::OutputFormat;
+use crate::context::Context;
+use crate::local_history::{HistoryEntry, LocalHistory};
+use crate::utils::pager;
+use anyhow::Result;
+use aws_sdk_athena::primitives::DateTime;
+use prettytable::{Cell, Row, Table};
+
+/// Lists queries from the local history log, most recent first
+pub fn list(ctx: &Context, args: &HistoryArgs) -> Result<()> {
+    let limit = usize::try_from(args.limit.unwrap_or_else(|| ctx.history_size())).unwrap_or(0);
+
+    let entries: Vec<HistoryEntry> = LocalHistory::open()?
+        .read()?
+        .into_iter()
+        .rev()
+        .filter(|entry| match args.status {
+            Some(status) => entry.state == status.as_str(),
+            None => true,
+        })
+        .filter(|entry| super::list::matches_grep(&entry.query, args.grep.as_deref()))
+        .take(limit)
+        .collect();
+
+    if entries.is_empty() {
+        eprintln!("No queries found in local history");
+        return Ok(());
+    }
+
+    if args.summary {
+        let mut summary = HistorySummary::default();
+        for entry in &entries {
+            summary.add(&entry.state, entry.data_scanned);
+        }
+        summary.print();
+        return Ok(());
+    }
+
+    if ctx.history_format(args.format.map(Into::into)) == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    let timestamps = ctx.timestamp_format();
+    let max_width = ctx.max_column_width();
+    let mut table = Table::new();
+    table.add_row(Row::new(
+        ["ID", "Status", "Query", "Workgroup", "Time", "Data Scanned"]
+            .iter()
+            .map(|header| Cell::new(header).style_spec("Fb"))
+            .collect(),
+    ));
+
+    for entry in &entries {
+        let time = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+            .map(|t| DateTime::from_secs(t.timestamp()).format_datetime(&timestamps))
+            .unwrap_or_else(|_| entry.timestamp.clone());
+
+        table.add_row(Row::new(
+            [
+                entry.query_execution_id.as_str(),
+                &entry.state,
+                &entry.query,
+                &entry.workgroup,
+                &time,
+                &entry.data_scanned.format_bytes_or_default(),
+            ]
+            .iter()
+            .map(|value| table_cell(value, max_width))
+            .collect(),
+        ));
+    }
+
+    pager::page_table(&table)?;
+    Ok(())
+}
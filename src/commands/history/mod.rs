@@ -1,4 +1,7 @@
 mod fields;
 mod list;
+mod local;
+pub mod pick;
+mod summary;
 
 pub use list::list;
@@ -0,0 +1,111 @@
+//! Picking a query execution from the recent history when a command that
+//! needs a query ID is run without one.
+
+use super::fields::{get_field_value, HistoryField};
+use crate::commands::common::truncate_value;
+use crate::context::Context;
+use crate::error::AthenaCliError;
+use anyhow::Result;
+use aws_sdk_athena::types::QueryExecution;
+use dialoguer::FuzzySelect;
+use std::io::{self, IsTerminal};
+
+/// Widest query text shown in the list
+const QUERY_WIDTH: usize = 60;
+
+/// Returns `query_id` when given. Otherwise, in an interactive terminal, lets
+/// the user pick one of the workgroup's recent executions with a fuzzy search
+/// over their IDs, statuses and query text.
+pub async fn query_id(ctx: &Context, query_id: Option<&str>) -> Result<String> {
+    if let Some(query_id) = query_id {
+        return Ok(query_id.to_string());
+    }
+    if !(io::stdin().is_terminal() && io::stderr().is_terminal()) {
+        return Err(AthenaCliError::Validation(
+            "A query execution ID is required when not running in an interactive terminal"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let client = ctx.create_athena_client();
+    let workgroup = ctx.workgroup();
+    let result = client
+        .list_query_executions()
+        .work_group(&workgroup)
+        .max_results(ctx.history_size())
+        .send()
+        .await?;
+    let query_ids = result.query_execution_ids();
+    if query_ids.is_empty() {
+        return Err(AthenaCliError::NotFound(format!(
+            "No queries found in workgroup: {}",
+            workgroup
+        ))
+        .into());
+    }
+
+    let details = client
+        .batch_get_query_execution()
+        .set_query_execution_ids(Some(query_ids.to_vec()))
+        .send()
+        .await?;
+    // Keep the most recent first, as listed
+    let executions: Vec<&QueryExecution> = query_ids
+        .iter()
+        .filter_map(|id| {
+            details
+                .query_executions()
+                .iter()
+                .find(|execution| execution.query_execution_id() == Some(id.as_str()))
+        })
+        .collect();
+
+    let timestamps = ctx.timestamp_format();
+    let price_per_tb = ctx.price_per_tb();
+    let labels: Vec<String> = executions
+        .iter()
+        .map(|execution| {
+            let field = |field| get_field_value(execution, field, &timestamps, price_per_tb);
+            format!(
+                "{}  {:<9}  {}  {}",
+                field(HistoryField::Id),
+                field(HistoryField::Status),
+                field(HistoryField::StartTime),
+                one_line(&field(HistoryField::Query))
+            )
+        })
+        .collect();
+
+    let selected = FuzzySelect::new()
+        .with_prompt(format!("Query execution in {} (type to search)", workgroup))
+        .items(&labels)
+        .default(0)
+        .interact_opt()?
+        .ok_or_else(|| AthenaCliError::Validation("No query execution selected".to_string()))?;
+
+    Ok(executions[selected]
+        .query_execution_id()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// The query text on a single line, shortened to fit the list
+fn one_line(query: &str) -> String {
+    let line = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate_value(&line, QUERY_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_line() {
+        assert_eq!(
+            one_line("SELECT id,\n       name\n  FROM  users"),
+            "SELECT id, name FROM users"
+        );
+        assert_eq!(one_line(&"x".repeat(80)).chars().count(), QUERY_WIDTH);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::commands::common::ByteDisplay;
+use crate::utils::table_style;
+use prettytable::{Cell, Row, Table};
+use std::collections::BTreeMap;
+
+/// Aggregate statistics over a window of query executions
+#[derive(Debug, Default)]
+pub struct HistorySummary {
+    total: usize,
+    by_status: BTreeMap<String, usize>,
+    data_scanned: i64,
+    scanned_queries: usize,
+}
+
+impl HistorySummary {
+    /// Adds one execution with the given state and scanned bytes, if reported
+    pub fn add(&mut self, state: &str, data_scanned: Option<i64>) {
+        self.total += 1;
+        *self.by_status.entry(state.to_string()).or_default() += 1;
+
+        if let Some(bytes) = data_scanned {
+            self.data_scanned += bytes;
+            self.scanned_queries += 1;
+        }
+    }
+
+    /// Average bytes scanned by the queries that reported it
+    pub fn average_data_scanned(&self) -> Option<i64> {
+        (self.scanned_queries > 0).then(|| self.data_scanned / self.scanned_queries as i64)
+    }
+
+    /// Percentage of queries that succeeded
+    pub fn success_rate(&self) -> Option<f64> {
+        let succeeded = self.by_status.get("SUCCEEDED").copied().unwrap_or(0);
+        (self.total > 0).then(|| succeeded as f64 * 100.0 / self.total as f64)
+    }
+
+    pub fn print(&self) {
+        let mut table = Table::new();
+
+        let mut add_row = |label: &str, value: String| {
+            table.add_row(Row::new(vec![
+                Cell::new(label).style_spec("Fb"),
+                Cell::new(&value),
+            ]));
+        };
+
+        add_row("Total Queries", self.total.to_string());
+        for (status, count) in &self.by_status {
+            add_row(status, count.to_string());
+        }
+        add_row("Total Data Scanned", self.data_scanned.format_bytes());
+        add_row(
+            "Average Data Scanned",
+            self.average_data_scanned()
+                .map_or_else(|| "-".to_string(), |bytes| bytes.format_bytes()),
+        );
+        add_row(
+            "Success Rate",
+            self.success_rate()
+                .map_or_else(|| "-".to_string(), |rate| format!("{:.1}%", rate)),
+        );
+
+        table_style::print(&table);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_summary() {
+        let mut summary = HistorySummary::default();
+        assert_eq!(summary.success_rate(), None);
+        assert_eq!(summary.average_data_scanned(), None);
+
+        summary.add("SUCCEEDED", Some(1_000));
+        summary.add("SUCCEEDED", Some(3_000));
+        summary.add("FAILED", None);
+        summary.add("CANCELLED", Some(2_000));
+
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.by_status.get("SUCCEEDED"), Some(&2));
+        assert_eq!(summary.data_scanned, 6_000);
+        // Only queries that reported scanned bytes count towards the average
+        assert_eq!(summary.average_data_scanned(), Some(2_000));
+        assert_eq!(summary.success_rate(), Some(50.0));
+    }
+}
@@ -1,44 +1,70 @@
-use super::download::download_from_s3;
+use super::download::{
+    download_from_s3, is_file_destination, read_manifest, DownloadOptions, STDOUT_OUTPUT,
+};
 use super::fields::{get_field_value, get_inspect_fields};
+use crate::cache::ResultCache;
 use crate::cli::InspectArgs;
+use crate::commands::{history, query};
 use crate::context::Context;
+use crate::utils::table_style;
 use anyhow::Result;
-use aws_sdk_s3;
-use owo_colors::OwoColorize;
-use prettytable::{format, Cell, Row, Table};
+use colored::Colorize;
+use prettytable::{Cell, Row, Table};
+use std::path::Path;
 
 pub async fn detail(ctx: &Context, args: &InspectArgs) -> Result<()> {
+    let show_progress = !(args.quiet || ctx.quiet());
+    inspect_query(ctx, args, show_progress).await
+}
+
+/// Shows query details and downloads results when requested. `show_progress`
+/// is separate from quiet mode so that `download` keeps its progress bar on
+/// stderr while still printing only file paths on stdout.
+pub(super) async fn inspect_query(
+    ctx: &Context,
+    args: &InspectArgs,
+    show_progress: bool,
+) -> Result<()> {
     let client = ctx.create_athena_client();
-    let query_id = args.query_id.clone();
+    let query_id = history::pick::query_id(ctx, args.query_id.as_deref()).await?;
+
+    // Streaming results to stdout leaves no room for anything else there
+    let to_stdout = args.output.as_deref() == Some(STDOUT_OUTPUT);
 
     // Command-specific quiet overrides global setting
-    let quiet_mode = args.quiet || ctx.quiet();
+    let quiet_mode = args.quiet || ctx.quiet() || to_stdout;
 
     if !quiet_mode {
         println!("\n{}", "Query Execution Details".bold());
         println!("ID: {}\n", query_id.bright_green());
     }
 
-    // Get query execution details
-    let result = client
-        .get_query_execution()
-        .query_execution_id(&query_id)
-        .send()
-        .await?;
-
-    let execution = result
-        .query_execution()
-        .ok_or_else(|| anyhow::anyhow!("No query execution found with ID: {}", query_id))?;
+    // Get query execution details, waiting for it to finish if requested
+    let execution = if args.wait {
+        if !quiet_mode {
+            eprintln!("Waiting for query to finish...\n");
+        }
+        query::wait_until_finished(&client, &query_id, args.timeout).await?
+    } else {
+        let result = client
+            .get_query_execution()
+            .query_execution_id(&query_id)
+            .send()
+            .await?;
+
+        result
+            .query_execution()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No query execution found with ID: {}", query_id))?
+    };
 
     if !quiet_mode {
         // Create a table for the query information
         let mut table = Table::new();
 
-        // Configure table style
-        table.set_format(*format::consts::FORMAT_CLEAN); // Clean borders
-
         // Get fields to display
         let fields = get_inspect_fields();
+        let timestamps = ctx.timestamp_format();
 
         // Add header
         table.add_row(Row::new(vec![
@@ -48,7 +74,7 @@ pub async fn detail(ctx: &Context, args: &InspectArgs) -> Result<()> {
 
         // Add rows for each field
         for field in fields {
-            let value = get_field_value(execution, field);
+            let value = get_field_value(&execution, field, &timestamps, ctx.price_per_tb());
             let formatted_value = match field.to_string().as_str() {
                 "Status" => match value.as_str() {
                     "SUCCEEDED" => value.bright_green().to_string(),
@@ -66,7 +92,7 @@ pub async fn detail(ctx: &Context, args: &InspectArgs) -> Result<()> {
         }
 
         // Print the table
-        table.printstd();
+        table_style::print(&table);
     }
 
     // Check if query was successful before trying to get results
@@ -85,36 +111,89 @@ pub async fn detail(ctx: &Context, args: &InspectArgs) -> Result<()> {
                     if !quiet_mode {
                         println!("\n{}", "S3 Output Location:".bold());
                         println!("📂 {}", s3_output_location.bright_blue());
-                        println!("\n{}", "Downloading Results...".bold());
+                        eprintln!("\n{}", "Downloading Results...".bold());
                     }
 
-                    let s3_client = aws_sdk_s3::Client::new(ctx.aws_config());
-
-                    match download_from_s3(&s3_client, s3_output_location, output_dir, &query_id)
-                        .await
+                    let s3_client = ctx.create_s3_client();
+                    let download_options = DownloadOptions {
+                        verbose: ctx.verbose(),
+                        progress: show_progress,
+                        decompress: args.decompress,
+                        force: args.force,
+                        filename: args.filename.clone(),
+                        cache: if args.no_cache {
+                            None
+                        } else {
+                            ResultCache::open().ok()
+                        },
+                    };
+
+                    // The result file, plus its metadata and any data files listed in
+                    // the manifest written by CTAS/INSERT INTO/UNLOAD queries
+                    let mut s3_urls = vec![s3_output_location.to_string()];
+                    if args.with_metadata && !to_stdout {
+                        s3_urls.push(format!("{}.metadata", s3_output_location));
+                    }
+                    if let Some(manifest_url) = execution
+                        .statistics()
+                        .and_then(|s| s.data_manifest_location())
                     {
-                        Ok(file_path) => {
-                            if quiet_mode {
-                                println!("{}", file_path.display());
-                            } else {
-                                println!(
-                                    "✅ Downloaded to: {}",
-                                    file_path.display().to_string().bright_green()
-                                )
-                            }
+                        match read_manifest(&s3_client, manifest_url).await {
+                            Ok(data_files) => s3_urls.extend(data_files),
+                            Err(e) if quiet_mode => return Err(e),
+                            Err(e) => eprintln!("❌ Error: {}", e.to_string().bright_red()),
                         }
-                        Err(e) => {
-                            if quiet_mode {
-                                return Err(e);
-                            } else {
-                                println!("❌ Error: {}", e.to_string().bright_red())
+                    }
+
+                    // Extra files land next to the result file, under their S3 names
+                    let extras_dir = if is_file_destination(output_dir) {
+                        Path::new(output_dir)
+                            .parent()
+                            .and_then(|p| p.to_str())
+                            .filter(|p| !p.is_empty())
+                            .unwrap_or(".")
+                            .to_string()
+                    } else {
+                        output_dir.clone()
+                    };
+                    let extra_options = DownloadOptions {
+                        filename: None,
+                        ..download_options.clone()
+                    };
+
+                    for (index, s3_url) in s3_urls.iter().enumerate() {
+                        let (output, options) = if index == 0 {
+                            (output_dir.as_str(), &download_options)
+                        } else {
+                            (extras_dir.as_str(), &extra_options)
+                        };
+
+                        match download_from_s3(&s3_client, s3_url, output, &query_id, options).await
+                        {
+                            Ok(_) if to_stdout => {}
+                            Ok(file_path) => {
+                                if quiet_mode {
+                                    println!("{}", file_path.display());
+                                } else {
+                                    eprintln!(
+                                        "✅ Downloaded to: {}",
+                                        file_path.display().to_string().bright_green()
+                                    )
+                                }
+                            }
+                            Err(e) => {
+                                if quiet_mode {
+                                    return Err(e);
+                                } else {
+                                    eprintln!("❌ Error: {}", e.to_string().bright_red())
+                                }
                             }
                         }
                     }
                 }
             } else if !quiet_mode {
-                println!("\n{}", "Cannot display results:".bold());
-                println!("❌ Query status is {}", state.as_str().bright_red());
+                eprintln!("\n{}", "Cannot display results:".bold());
+                eprintln!("❌ Query status is {}", state.as_str().bright_red());
             }
         }
     }
@@ -1,77 +1,151 @@
+use crate::aws::s3::parse_s3_url;
+use crate::cache::{CacheEntry, CacheWriter, ResultCache};
+use crate::error::AthenaCliError;
 use anyhow::{anyhow, Context, Result};
+use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::Client;
+use flate2::write::GzDecoder;
+use indicatif::{ProgressBar, ProgressStyle};
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{self, BufWriter, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use url::Url;
+use std::time::Duration;
 
-/// Downloads a query result file from S3 to the specified output directory
-pub async fn download_from_s3(
-    s3_client: &Client,
-    s3_url: &str,
-    output_dir: &str,
-    _query_id: &str,
-) -> Result<PathBuf> {
-    println!("Downloading query results from S3: {}", s3_url);
-
-    // Parse the S3 URL to extract bucket and key
-    let url = Url::parse(s3_url).context(format!("Failed to parse S3 URL: {}", s3_url))?;
-
-    // Log the URL components for debugging
-    println!(
-        "URL scheme: {}, host: {:?}, path: {}",
-        url.scheme(),
-        url.host_str(),
-        url.path()
-    );
-
-    let host = url
-        .host_str()
-        .ok_or_else(|| anyhow!("Invalid S3 URL: no host in {}", s3_url))?;
-
-    // Handle different S3 URL formats
-    let (bucket, key) = if let Some(stripped) = s3_url.strip_prefix("s3://") {
-        // s3://bucket-name/key format
-        let parts: Vec<&str> = stripped.splitn(2, '/').collect();
-
-        if parts.len() < 2 {
-            return Err(anyhow!("Invalid S3 URL format (s3://): {}", s3_url));
+/// Options controlling how result files are downloaded
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Print progress details to stderr
+    pub verbose: bool,
+    /// Show a progress bar on stderr (only when stderr is a terminal)
+    pub progress: bool,
+    /// Decompress gzip results and strip the `.gz` suffix
+    pub decompress: bool,
+    /// Overwrite existing files instead of refusing
+    pub force: bool,
+    /// Explicit name for the downloaded file instead of the one derived from the S3 key
+    pub filename: Option<String>,
+    /// Local cache to serve unchanged objects from and store new downloads in
+    pub cache: Option<ResultCache>,
+}
+
+/// Output value that streams results to stdout instead of a file
+pub const STDOUT_OUTPUT: &str = "-";
+
+/// Number of times an interrupted download is resumed before giving up
+const MAX_DOWNLOAD_RETRIES: u32 = 3;
+
+/// Delay before the first resume attempt, doubled for each further attempt
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Destination for downloaded bytes, optionally gunzipping on the fly
+enum OutputWriter {
+    Plain(Box<dyn Write>),
+    Gzip(Box<GzDecoder<Box<dyn Write>>>),
+}
+
+impl OutputWriter {
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush(),
+            Self::Gzip(w) => w.finish()?.flush(),
         }
+    }
+}
 
-        (parts[0].to_string(), parts[1].to_string())
-    } else if host.ends_with(".amazonaws.com") {
-        // https://bucket-name.s3.region.amazonaws.com/key format
-        let bucket_name = host
-            .split('.')
-            .next()
-            .ok_or_else(|| anyhow!("Invalid S3 URL: cannot extract bucket from host: {}", host))?;
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gzip(w) => w.write(buf),
+        }
+    }
 
-        // Remove leading slash from path
-        let object_key = url.path().strip_prefix('/').unwrap_or(url.path());
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gzip(w) => w.flush(),
+        }
+    }
+}
 
-        (bucket_name.to_string(), object_key.to_string())
-    } else {
-        // https://s3.region.amazonaws.com/bucket-name/key format
-        let path_segments = url
-            .path_segments()
-            .ok_or_else(|| anyhow!("Invalid S3 URL: no path in {}", s3_url))?
-            .collect::<Vec<_>>();
-
-        if path_segments.is_empty() {
-            return Err(anyhow!("Invalid S3 URL: empty path in {}", s3_url));
+/// A download written under a temporary name next to its destination and moved
+/// into place by [`commit`](Self::commit) once complete. Dropped without being
+/// committed, e.g. when the download fails, the partial file is removed so a
+/// truncated file is never left at the destination.
+struct PartialFile {
+    partial_path: PathBuf,
+    path: PathBuf,
+    committed: bool,
+}
+
+impl PartialFile {
+    fn new(path: &Path) -> Self {
+        let mut partial_path = path.as_os_str().to_owned();
+        partial_path.push(".partial");
+        Self {
+            partial_path: PathBuf::from(partial_path),
+            path: path.to_path_buf(),
+            committed: false,
         }
+    }
 
-        let bucket_name = path_segments[0];
-        let object_key = path_segments[1..].join("/");
+    /// Moves the finished download to its destination
+    fn commit(mut self) -> Result<()> {
+        fs::rename(&self.partial_path, &self.path).context(format!(
+            "Failed to move download into place: {}",
+            self.path.display()
+        ))?;
+        self.committed = true;
+        Ok(())
+    }
+}
 
-        (bucket_name.to_string(), object_key)
-    };
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.partial_path);
+        }
+    }
+}
 
-    println!("Extracted bucket: {}, key: {}", bucket, key);
+/// Where the bytes of a downloaded object come from
+enum ObjectBody {
+    Remote(ByteStream),
+    Cached(File),
+}
 
-    // Create output directory if it doesn't exist
-    fs::create_dir_all(output_dir)
-        .context(format!("Failed to create output directory: {}", output_dir))?;
+/// An S3 object ready to be written out
+struct FetchedObject {
+    body: ObjectBody,
+    content_length: Option<i64>,
+    content_encoding: Option<String>,
+    etag: Option<String>,
+}
+
+/// Downloads a query result file from S3 to the specified output directory,
+/// or to `output` itself when it names a file (see [`is_file_destination`]).
+/// When `output` is [`STDOUT_OUTPUT`] the bytes are written to stdout unchanged.
+/// The body is streamed in chunks rather than buffered in memory. With a cache
+/// in `options`, an unchanged object (same ETag and size) is served from disk.
+pub async fn download_from_s3(
+    s3_client: &Client,
+    s3_url: &str,
+    output: &str,
+    query_id: &str,
+    options: &DownloadOptions,
+) -> Result<PathBuf> {
+    let to_stdout = output == STDOUT_OUTPUT;
+    // Diagnostics go to stderr so stdout only ever carries results or paths
+    let verbose = options.verbose;
+    if verbose {
+        eprintln!("Downloading query results from S3: {}", s3_url);
+    }
+
+    let (bucket, key) = parse_s3_url(s3_url)?;
+
+    if verbose {
+        eprintln!("Extracted bucket: {}, key: {}", bucket, key);
+    }
 
     // Extract filename from the key
     let filename_from_key = Path::new(&key)
@@ -80,53 +154,450 @@ pub async fn download_from_s3(
         .to_string_lossy()
         .to_string();
 
-    // Create output file path
-    let output_path = Path::new(output_dir).join(&filename_from_key);
-    println!("Will save to: {}", output_path.display());
+    // Reuse a cached copy if S3 still reports the same ETag and size
+    let cached = options
+        .cache
+        .as_ref()
+        .and_then(|cache| cache.lookup(query_id, &filename_from_key));
+    let object = match cached {
+        Some(cached) => {
+            let head = s3_client
+                .head_object()
+                .bucket(&bucket)
+                .key(&key)
+                .send()
+                .await
+                .map_err(AthenaCliError::from_s3)
+                .context(format!(
+                    "Failed to check S3 object, bucket: {}, key: {}",
+                    bucket, key
+                ))?;
+
+            if cached.is_fresh(head.e_tag(), head.content_length()) {
+                if verbose {
+                    eprintln!("Using cached copy: {}", cached.path.display());
+                }
+                let file = File::open(&cached.path).context(format!(
+                    "Failed to open cached file: {}",
+                    cached.path.display()
+                ))?;
+                FetchedObject {
+                    body: ObjectBody::Cached(file),
+                    content_length: head.content_length(),
+                    content_encoding: head.content_encoding().map(str::to_string),
+                    etag: None,
+                }
+            } else {
+                if verbose {
+                    eprintln!("Cached copy is out of date");
+                }
+                get_object(s3_client, &bucket, &key, verbose).await?
+            }
+        }
+        None => get_object(s3_client, &bucket, &key, verbose).await?,
+    };
+
+    // Only gunzip when asked to, and when the object is actually gzip-compressed
+    let gunzip = options.decompress
+        && (key.ends_with(".gz")
+            || object
+                .content_encoding
+                .as_deref()
+                .is_some_and(|encoding| encoding.eq_ignore_ascii_case("gzip")));
+    let filename = if gunzip {
+        filename_from_key
+            .strip_suffix(".gz")
+            .unwrap_or(&filename_from_key)
+    } else {
+        &filename_from_key
+    };
+
+    let mut partial = None;
+    let (output_path, file): (PathBuf, Box<dyn Write>) = if to_stdout {
+        (
+            PathBuf::from(STDOUT_OUTPUT),
+            Box::new(BufWriter::new(io::stdout().lock())),
+        )
+    } else {
+        // Create output file path
+        let output_path = resolve_output_path(output, options.filename.as_deref(), filename);
+        if verbose {
+            eprintln!("Will save to: {}", output_path.display());
+        }
+
+        // Create output directory if it doesn't exist
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).context(format!(
+                "Failed to create output directory: {}",
+                parent.display()
+            ))?;
+        }
+        ensure_can_write(&output_path, options.force)?;
+
+        // Written under a temporary name until complete, see PartialFile
+        let download = PartialFile::new(&output_path);
+        let file = File::create(&download.partial_path).context(format!(
+            "Failed to create output file: {}",
+            download.partial_path.display()
+        ))?;
+        partial = Some(download);
+        (output_path, Box::new(BufWriter::new(file)))
+    };
+
+    let progress = if options.progress && io::stderr().is_terminal() {
+        progress_bar(object.content_length)
+    } else {
+        ProgressBar::hidden()
+    };
+
+    let mut writer = if gunzip {
+        OutputWriter::Gzip(Box::new(GzDecoder::new(file)))
+    } else {
+        OutputWriter::Plain(file)
+    };
+
+    let mut total_bytes: u64 = 0;
+    match object.body {
+        ObjectBody::Remote(mut body) => {
+            // Keep a raw copy of new downloads in the cache
+            let mut cache_writer = cache_writer(
+                options,
+                query_id,
+                &filename_from_key,
+                object.etag.as_deref(),
+                object.content_length,
+            );
+
+            // Stream the body to the file chunk by chunk, resuming from the
+            // bytes already written if the connection drops
+            let mut retries = 0;
+            loop {
+                let chunk = match body.try_next().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    // Nothing left to resume once every byte has arrived
+                    Err(_) if object.content_length == i64::try_from(total_bytes).ok() => break,
+                    Err(e) => {
+                        let error =
+                            anyhow::Error::new(e).context("Failed to read S3 object data stream");
+                        body = resume_object(
+                            s3_client,
+                            &bucket,
+                            &key,
+                            object.etag.as_deref(),
+                            total_bytes,
+                            &mut retries,
+                            error,
+                        )
+                        .await?;
+                        continue;
+                    }
+                };
+                writer.write_all(&chunk).context(format!(
+                    "Failed to write data to file: {}",
+                    output_path.display()
+                ))?;
+                // A failing cache must never fail the download itself
+                if cache_writer
+                    .as_mut()
+                    .is_some_and(|cache| cache.write_all(&chunk).is_err())
+                {
+                    cache_writer = None;
+                }
+                total_bytes += chunk.len() as u64;
+                progress.inc(chunk.len() as u64);
+            }
 
-    // Get the object from S3
-    println!("Requesting object from S3...");
+            if let Some(Err(e)) = cache_writer.map(CacheWriter::commit) {
+                if verbose {
+                    eprintln!("Failed to cache download: {}", e);
+                }
+            }
+        }
+        ObjectBody::Cached(file) => {
+            total_bytes = io::copy(&mut progress.wrap_read(file), &mut writer).context(format!(
+                "Failed to write data to file: {}",
+                output_path.display()
+            ))?;
+        }
+    }
+    writer.finish().context(format!(
+        "Failed to write data to file: {}",
+        output_path.display()
+    ))?;
+    if let Some(partial) = partial {
+        partial.commit()?;
+    }
+    progress.finish_and_clear();
+
+    if verbose {
+        eprintln!(
+            "Successfully downloaded {} bytes to {}",
+            total_bytes,
+            output_path.display()
+        );
+    }
+
+    Ok(output_path)
+}
+
+/// Requests the object from S3
+async fn get_object(
+    s3_client: &Client,
+    bucket: &str,
+    key: &str,
+    verbose: bool,
+) -> Result<FetchedObject> {
+    if verbose {
+        eprintln!("Requesting object from S3...");
+    }
     let resp = s3_client
         .get_object()
-        .bucket(&bucket)
-        .key(&key)
+        .bucket(bucket)
+        .key(key)
         .send()
         .await
+        .map_err(AthenaCliError::from_s3)
         .context(format!(
             "Failed to download file from S3 bucket: {}, key: {}",
             bucket, key
         ))?;
 
-    println!(
-        "S3 response received, content length: {:?}",
-        resp.content_length()
-    );
+    if verbose {
+        eprintln!(
+            "S3 response received, content length: {:?}",
+            resp.content_length()
+        );
+    }
+
+    Ok(FetchedObject {
+        content_length: resp.content_length(),
+        content_encoding: resp.content_encoding().map(str::to_string),
+        etag: resp.e_tag().map(str::to_string),
+        body: ObjectBody::Remote(resp.body),
+    })
+}
+
+/// Requests the rest of an object, from byte `offset` on, after its stream
+/// failed. Retries with a growing delay until [`MAX_DOWNLOAD_RETRIES`] resumes
+/// of this download have been used up. The ETag is required to match so the
+/// remaining bytes can't come from a newer version of the object.
+async fn resume_object(
+    s3_client: &Client,
+    bucket: &str,
+    key: &str,
+    etag: Option<&str>,
+    offset: u64,
+    retries: &mut u32,
+    mut error: anyhow::Error,
+) -> Result<ByteStream> {
+    loop {
+        if *retries >= MAX_DOWNLOAD_RETRIES {
+            return Err(error.context(format!(
+                "Download of s3://{}/{} failed after {} retries ({} bytes received)",
+                bucket, key, MAX_DOWNLOAD_RETRIES, offset
+            )));
+        }
+        *retries += 1;
+
+        eprintln!(
+            "Download interrupted after {} bytes ({:#}), resuming (retry {}/{})",
+            offset, error, retries, MAX_DOWNLOAD_RETRIES
+        );
+        tokio::time::sleep(RETRY_DELAY * 2u32.pow(*retries - 1)).await;
+
+        match s3_client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .range(format!("bytes={}-", offset))
+            .set_if_match(etag.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(resp) => return Ok(resp.body),
+            Err(e) => {
+                error = anyhow::Error::new(AthenaCliError::from_s3(e))
+                    .context("Failed to resume download")
+            }
+        }
+    }
+}
+
+/// Opens a cache copy for a fresh download, if caching is enabled and the
+/// object has the ETag and size needed to validate it later
+fn cache_writer(
+    options: &DownloadOptions,
+    query_id: &str,
+    filename: &str,
+    etag: Option<&str>,
+    size: Option<i64>,
+) -> Option<CacheWriter> {
+    let entry = CacheEntry {
+        etag: etag?.to_string(),
+        size: size?,
+    };
+    let cache = options.cache.as_ref()?;
+
+    match cache.writer(query_id, filename, entry) {
+        Ok(writer) => Some(writer),
+        Err(e) => {
+            if options.verbose {
+                eprintln!("Not caching download: {}", e);
+            }
+            None
+        }
+    }
+}
+
+/// Whether `output` names a destination file rather than a directory.
+/// Existing directories and paths with a trailing separator are directories;
+/// otherwise a path with a file extension (e.g. `results.csv`) is a file.
+pub fn is_file_destination(output: &str) -> bool {
+    let path = Path::new(output);
+    if output.ends_with('/') || output.ends_with(std::path::MAIN_SEPARATOR) || path.is_dir() {
+        return false;
+    }
+    path.extension().is_some()
+}
+
+/// Computes where a download is written: an explicit `filename` inside the
+/// `output` directory, `output` itself when it is a file path, or the
+/// key-derived name inside the `output` directory
+fn resolve_output_path(output: &str, filename: Option<&str>, key_filename: &str) -> PathBuf {
+    match filename {
+        Some(name) => Path::new(output).join(name),
+        None if is_file_destination(output) => PathBuf::from(output),
+        None => Path::new(output).join(key_filename),
+    }
+}
+
+/// Refuses to clobber an existing file unless `force` is set
+fn ensure_can_write(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(anyhow!(
+            "Output file already exists: {}. Use --force to overwrite it",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Creates a byte progress bar, or a spinner when the size is unknown
+fn progress_bar(content_length: Option<i64>) -> ProgressBar {
+    match content_length.and_then(|len| u64::try_from(len).ok()) {
+        Some(len) => ProgressBar::new(len).with_style(
+            ProgressStyle::with_template(
+                "{spinner} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .expect("valid progress template")
+            .progress_chars("=> "),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("{spinner} {bytes} ({bytes_per_sec})")
+                .expect("valid progress template"),
+        ),
+    }
+}
+
+/// Reads an Athena data manifest (written for CTAS, INSERT INTO and UNLOAD queries)
+/// and returns the S3 URLs of the data files it references, one per line.
+pub async fn read_manifest(s3_client: &Client, manifest_url: &str) -> Result<Vec<String>> {
+    let (bucket, key) = parse_s3_url(manifest_url)?;
+
+    let resp = s3_client
+        .get_object()
+        .bucket(&bucket)
+        .key(&key)
+        .send()
+        .await
+        .map_err(AthenaCliError::from_s3)
+        .context(format!("Failed to read data manifest: {}", manifest_url))?;
 
-    // Read the data
     let data = resp
         .body
         .collect()
         .await
         .context("Failed to read S3 object data stream")?;
-    let bytes = data.into_bytes();
+    let content = String::from_utf8_lossy(&data.into_bytes()).to_string();
 
-    println!("Downloaded {} bytes from S3", bytes.len());
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
 
-    // Write to file
-    let mut file = File::create(&output_path).context(format!(
-        "Failed to create output file: {}",
-        output_path.display()
-    ))?;
-    file.write_all(&bytes).context(format!(
-        "Failed to write data to file: {}",
-        output_path.display()
-    ))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!(
-        "Successfully downloaded {} bytes to {}",
-        bytes.len(),
-        output_path.display()
-    );
+    #[test]
+    fn test_resolve_output_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
 
-    Ok(output_path)
+        // Directories get the key-derived name
+        assert_eq!(
+            resolve_output_path(dir_str, None, "abc.csv"),
+            dir.path().join("abc.csv")
+        );
+        assert_eq!(
+            resolve_output_path("out/", None, "abc.csv"),
+            Path::new("out/").join("abc.csv")
+        );
+        assert_eq!(
+            resolve_output_path(".", None, "abc.csv"),
+            Path::new(".").join("abc.csv")
+        );
+
+        // A file path is used as the full destination
+        assert_eq!(
+            resolve_output_path("exports/report.csv", None, "abc.csv"),
+            PathBuf::from("exports/report.csv")
+        );
+
+        // An explicit filename wins over the key-derived one
+        assert_eq!(
+            resolve_output_path(dir_str, Some("daily.csv"), "abc.csv"),
+            dir.path().join("daily.csv")
+        );
+    }
+
+    #[test]
+    fn test_ensure_can_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+
+        // A new file is always fine
+        assert!(ensure_can_write(&path, false).is_ok());
+
+        // An existing file collides unless forced
+        fs::write(&path, "id\n1\n").unwrap();
+        let err = ensure_can_write(&path, false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(ensure_can_write(&path, true).is_ok());
+    }
+
+    #[test]
+    fn test_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+
+        // A failed download leaves nothing behind
+        let partial = PartialFile::new(&path);
+        fs::write(&partial.partial_path, "id\n").unwrap();
+        drop(partial);
+        assert!(!path.exists());
+        assert!(!dir.path().join("results.csv.partial").exists());
+
+        // A finished one replaces the destination
+        fs::write(&path, "old\n").unwrap();
+        let partial = PartialFile::new(&path);
+        fs::write(&partial.partial_path, "id\n1\n").unwrap();
+        partial.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "id\n1\n");
+        assert!(!dir.path().join("results.csv.partial").exists());
+    }
 }
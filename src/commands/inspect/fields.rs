@@ -1,4 +1,7 @@
-use crate::commands::common::{OptionByteDisplay, OptionDisplayValue, OptionDurationFormat};
+use crate::commands::common::{
+    engine_version, execution_cost, format_cost, used_result_cache, OptionByteDisplay,
+    OptionDateTimeFormat, OptionDisplayValue, OptionDurationFormat, ResultReuse, TimestampFormat,
+};
 use crate::config;
 use aws_sdk_athena::types::QueryExecution;
 use std::fmt;
@@ -16,8 +19,11 @@ pub enum InspectField {
     Database,
     Catalog,
     Workgroup,
+    EngineVersion,
     DataScanned,
+    EstimatedCost,
     CacheStatus,
+    ResultReuse,
     EngineExecutionTime,
     TotalExecutionTime,
     QueryPlanningTime,
@@ -41,8 +47,11 @@ impl FromStr for InspectField {
             "Database" => Ok(InspectField::Database),
             "Catalog" => Ok(InspectField::Catalog),
             "Workgroup" => Ok(InspectField::Workgroup),
+            "EngineVersion" => Ok(InspectField::EngineVersion),
             "DataScanned" => Ok(InspectField::DataScanned),
+            "EstimatedCost" => Ok(InspectField::EstimatedCost),
             "CacheStatus" => Ok(InspectField::CacheStatus),
+            "ResultReuse" => Ok(InspectField::ResultReuse),
             "EngineExecutionTime" => Ok(InspectField::EngineExecutionTime),
             "TotalExecutionTime" => Ok(InspectField::TotalExecutionTime),
             "QueryPlanningTime" => Ok(InspectField::QueryPlanningTime),
@@ -66,8 +75,11 @@ impl fmt::Display for InspectField {
             InspectField::Database => write!(f, "Database"),
             InspectField::Catalog => write!(f, "Catalog"),
             InspectField::Workgroup => write!(f, "Workgroup"),
+            InspectField::EngineVersion => write!(f, "Engine Version"),
             InspectField::DataScanned => write!(f, "Data Scanned"),
+            InspectField::EstimatedCost => write!(f, "Estimated Cost"),
             InspectField::CacheStatus => write!(f, "Cache Status"),
+            InspectField::ResultReuse => write!(f, "Result Reuse"),
             InspectField::EngineExecutionTime => write!(f, "Engine Execution Time"),
             InspectField::TotalExecutionTime => write!(f, "Total Execution Time"),
             InspectField::QueryPlanningTime => write!(f, "Query Planning Time"),
@@ -90,8 +102,11 @@ pub fn default_inspect_fields() -> Vec<InspectField> {
         InspectField::Database,
         InspectField::Catalog,
         InspectField::Workgroup,
+        InspectField::EngineVersion,
         InspectField::DataScanned,
+        InspectField::EstimatedCost,
         InspectField::CacheStatus,
+        InspectField::ResultReuse,
         InspectField::EngineExecutionTime,
         InspectField::TotalExecutionTime,
         InspectField::QueryPlanningTime,
@@ -103,7 +118,7 @@ pub fn default_inspect_fields() -> Vec<InspectField> {
 
 // Get fields from config or use defaults
 pub fn get_inspect_fields() -> Vec<InspectField> {
-    if let Ok(config) = config::Config::load() {
+    if let Ok(config) = config::Config::load(false) {
         if let Some(field_names) = config.app.inspect_fields {
             let fields: Vec<InspectField> = field_names
                 .iter()
@@ -121,7 +136,12 @@ pub fn get_inspect_fields() -> Vec<InspectField> {
 }
 
 // Extract a field value from a query execution
-pub fn get_field_value(execution: &QueryExecution, field: InspectField) -> String {
+pub fn get_field_value(
+    execution: &QueryExecution,
+    field: InspectField,
+    timestamps: &TimestampFormat,
+    price_per_tb: f64,
+) -> String {
     match field {
         InspectField::Id => execution.query_execution_id().to_display_value_or_default(),
 
@@ -141,12 +161,12 @@ pub fn get_field_value(execution: &QueryExecution, field: InspectField) -> Strin
         InspectField::SubmissionTime => execution
             .status()
             .and_then(|s| s.submission_date_time())
-            .to_display_value_or_default(),
+            .format_datetime_or_default(timestamps),
 
         InspectField::CompletionTime => execution
             .status()
             .and_then(|s| s.completion_date_time())
-            .to_display_value_or_default(),
+            .format_datetime_or_default(timestamps),
 
         InspectField::Database => execution
             .query_execution_context()
@@ -160,24 +180,29 @@ pub fn get_field_value(execution: &QueryExecution, field: InspectField) -> Strin
 
         InspectField::Workgroup => execution.work_group().to_display_value_or_default(),
 
+        InspectField::EngineVersion => engine_version(execution).to_display_value_or_default(),
+
         InspectField::DataScanned => execution
             .statistics()
             .and_then(|s| s.data_scanned_in_bytes())
             .format_bytes_or_default(),
 
-        InspectField::CacheStatus => {
-            let data_scanned = execution
-                .statistics()
-                .and_then(|s| s.data_scanned_in_bytes())
-                .unwrap_or(1);
+        InspectField::EstimatedCost => execution_cost(execution, price_per_tb)
+            .map(format_cost)
+            .to_display_value_or_default(),
 
-            if data_scanned == 0 {
+        InspectField::CacheStatus => {
+            if used_result_cache(execution) {
                 "Used cache".to_string()
             } else {
                 "Fresh execution".to_string()
             }
         }
 
+        InspectField::ResultReuse => ResultReuse::from_execution(execution)
+            .map(|reuse| reuse.to_string())
+            .to_display_value_or_default(),
+
         InspectField::EngineExecutionTime => execution
             .statistics()
             .and_then(|s| s.engine_execution_time_in_millis())
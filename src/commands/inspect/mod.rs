@@ -10,13 +10,21 @@ use anyhow::Result;
 
 pub async fn download(ctx: &Context, args: &DownloadArgs) -> Result<()> {
     // Create inspect args with forced quiet mode
-    detail::detail(
+    detail::inspect_query(
         ctx,
         &InspectArgs {
             query_id: args.query_id.clone(),
             output: args.output.clone(),
+            filename: args.filename.clone(),
             quiet: true, // Always quiet for downloads
+            wait: false,
+            timeout: None,
+            with_metadata: args.with_metadata,
+            decompress: args.decompress,
+            force: args.force,
+            no_cache: args.no_cache,
         },
+        !ctx.quiet(),
     )
     .await
 }
@@ -0,0 +1,162 @@
+//! A local library of `.sql` files that can be run by name with `query --name`,
+//! a personal alternative to Athena's named queries.
+
+use crate::cli::LibraryArgs;
+use crate::context::Context;
+use crate::error::AthenaCliError;
+use crate::utils::table_style;
+use anyhow::{Context as _, Result};
+use prettytable::{Cell, Row, Table};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extension of the files making up the library
+const SQL_EXTENSION: &str = "sql";
+
+/// Lists the `.sql` files in the library directory, with the first comment line
+/// of each as its description
+pub fn list(ctx: &Context, args: &LibraryArgs) -> Result<()> {
+    let dir = library_dir(ctx, args.sql_file_dir.as_deref())?;
+    let entries = entries(&dir)?;
+
+    if entries.is_empty() {
+        println!("No .sql files found in {}", dir.display());
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Name").style_spec("Fb"),
+        Cell::new("Description").style_spec("Fb"),
+    ]));
+    for (name, path) in &entries {
+        let description = fs::read_to_string(path)
+            .ok()
+            .and_then(|sql| description(&sql))
+            .unwrap_or_else(|| "-".to_string());
+        table.add_row(Row::new(vec![Cell::new(name), Cell::new(&description)]));
+    }
+
+    table_style::print(&table);
+    Ok(())
+}
+
+/// Reads the SQL of the library file called `name` (with or without `.sql`)
+pub fn load(ctx: &Context, sql_file_dir: Option<&str>, name: &str) -> Result<String> {
+    let dir = library_dir(ctx, sql_file_dir)?;
+    let file_name = match Path::new(name).extension() {
+        Some(ext) if ext == SQL_EXTENSION => name.to_string(),
+        _ => format!("{}.{}", name, SQL_EXTENSION),
+    };
+    let path = dir.join(&file_name);
+
+    if !path.is_file() {
+        let available: Vec<String> = entries(&dir)?.into_iter().map(|(name, _)| name).collect();
+        let hint = if available.is_empty() {
+            String::new()
+        } else {
+            format!(". Available: {}", available.join(", "))
+        };
+        return Err(AthenaCliError::NotFound(format!(
+            "No query named '{}' in {}{}",
+            name,
+            dir.display(),
+            hint
+        ))
+        .into());
+    }
+
+    fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// The library directory from --sql-file-dir, or else app.query_library_dir
+fn library_dir(ctx: &Context, sql_file_dir: Option<&str>) -> Result<PathBuf> {
+    let dir = sql_file_dir
+        .map(PathBuf::from)
+        .or_else(|| ctx.query_library_dir())
+        .ok_or_else(|| {
+            AthenaCliError::Validation(
+                "No query library configured. Set app.query_library_dir in the config file \
+                 or pass --sql-file-dir"
+                    .to_string(),
+            )
+        })?;
+
+    if !dir.is_dir() {
+        return Err(AthenaCliError::NotFound(format!(
+            "Query library directory not found: {}",
+            dir.display()
+        ))
+        .into());
+    }
+    Ok(dir)
+}
+
+/// The library's queries as (name, path) pairs sorted by name
+fn entries(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut entries: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == SQL_EXTENSION))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some((name, path))
+        })
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// The text of the file's first comment line, if it starts with one
+fn description(sql: &str) -> Option<String> {
+    let line = sql.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let text = if let Some(text) = line.strip_prefix("--") {
+        text
+    } else {
+        let text = line.strip_prefix("/*")?;
+        text.split("*/").next().unwrap_or(text)
+    };
+
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description() {
+        assert_eq!(
+            description("-- Daily orders by region\nSELECT 1"),
+            Some("Daily orders by region".to_string())
+        );
+        assert_eq!(
+            description("\n  /* Revenue report */\nSELECT 1"),
+            Some("Revenue report".to_string())
+        );
+        assert_eq!(
+            description("/* Spans\n lines */ SELECT 1"),
+            Some("Spans".to_string())
+        );
+        assert_eq!(description("SELECT 1 -- trailing"), None);
+        assert_eq!(description("--\nSELECT 1"), None);
+        assert_eq!(description(""), None);
+    }
+
+    #[test]
+    fn test_entries_lists_only_sql_files_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b_report.sql"), "SELECT 2").unwrap();
+        fs::write(dir.path().join("a_report.sql"), "SELECT 1").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a query").unwrap();
+        fs::create_dir(dir.path().join("nested.sql")).unwrap();
+
+        let names: Vec<String> = entries(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["a_report", "b_report"]);
+    }
+}
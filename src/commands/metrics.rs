@@ -0,0 +1,243 @@
+//! A summary of the query metrics Athena publishes to CloudWatch for workgroups
+//! with `PublishCloudWatchMetricsEnabled`, as a quick health check.
+
+use crate::cli::MetricsArgs;
+use crate::commands::common::{ByteDisplay, DurationFormat};
+use crate::context::Context;
+use crate::error::AthenaCliError;
+use crate::utils::table_style;
+use anyhow::{Context as _, Result};
+use aws_sdk_cloudwatch::primitives::DateTime;
+use aws_sdk_cloudwatch::types::MetricDataQuery;
+use prettytable::{Cell, Row, Table};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// CloudWatch namespace of Athena's query metrics
+const NAMESPACE: &str = "AWS/Athena";
+
+/// The series fetched, as (query ID, metric name, statistic). Averages are
+/// computed from the sums and sample counts so every query weighs the same.
+const SERIES: [(&str, &str, &str); 7] = [
+    ("queries", "TotalExecutionTime", "SampleCount"),
+    ("processed_bytes", "ProcessedBytes", "Sum"),
+    ("execution_sum", "TotalExecutionTime", "Sum"),
+    ("execution_max", "TotalExecutionTime", "Maximum"),
+    ("queue_count", "QueryQueueTime", "SampleCount"),
+    ("queue_sum", "QueryQueueTime", "Sum"),
+    ("queue_max", "QueryQueueTime", "Maximum"),
+];
+
+pub async fn metrics(ctx: &Context, args: &MetricsArgs) -> Result<()> {
+    let workgroup = args.name.clone().unwrap_or_else(|| ctx.workgroup());
+    let period = period_seconds(args.period)?;
+
+    let enabled = ctx
+        .create_athena_client()
+        .get_work_group()
+        .work_group(&workgroup)
+        .send()
+        .await?
+        .work_group()
+        .and_then(|w| w.configuration())
+        .and_then(|c| c.publish_cloud_watch_metrics_enabled())
+        .unwrap_or(false);
+    if !enabled {
+        println!(
+            "Workgroup {} doesn't publish CloudWatch metrics. Turn on \"Publish query \
+             metrics to AWS CloudWatch\" in its settings to collect them.",
+            workgroup
+        );
+        return Ok(());
+    }
+
+    let queries: Vec<MetricDataQuery> = SERIES
+        .iter()
+        .map(|(id, metric, statistic)| {
+            MetricDataQuery::builder()
+                .id(*id)
+                .expression(search_expression(&workgroup, metric, statistic, period))
+                .return_data(true)
+                .build()
+        })
+        .collect();
+
+    let client = ctx.create_cloudwatch_client();
+    let end = SystemTime::now();
+    let start = end - args.since;
+    let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut next_token = None;
+    loop {
+        let output = client
+            .get_metric_data()
+            .start_time(DateTime::from(start))
+            .end_time(DateTime::from(end))
+            .set_metric_data_queries(Some(queries.clone()))
+            .set_next_token(next_token)
+            .send()
+            .await
+            .context("Failed to fetch CloudWatch metrics")?;
+
+        for result in output.metric_data_results() {
+            if let Some(id) = result.id() {
+                values
+                    .entry(id.to_string())
+                    .or_default()
+                    .extend(result.values());
+            }
+        }
+
+        next_token = output.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    let since = humantime::format_duration(args.since);
+    let summary = MetricsSummary::from_values(&values);
+    if summary.queries == 0 {
+        println!(
+            "No queries recorded for workgroup {} in the last {}",
+            workgroup, since
+        );
+        return Ok(());
+    }
+
+    println!(
+        "CloudWatch metrics for workgroup {} over the last {}",
+        workgroup, since
+    );
+    table_style::print(&summary.table());
+    Ok(())
+}
+
+/// The period in whole seconds, which CloudWatch needs to be a multiple of 60
+fn period_seconds(period: Duration) -> Result<i32> {
+    let seconds = period.as_secs();
+    if seconds == 0 || !seconds.is_multiple_of(60) || period.subsec_nanos() != 0 {
+        return Err(AthenaCliError::Validation(format!(
+            "--period must be a whole number of minutes, got {}",
+            humantime::format_duration(period)
+        ))
+        .into());
+    }
+    i32::try_from(seconds).context("--period is too long")
+}
+
+/// A metric math expression for one statistic of a metric across every query
+/// state and type in the workgroup, combined into a single series: the
+/// largest value for maximums, the total otherwise
+fn search_expression(workgroup: &str, metric: &str, statistic: &str, period: i32) -> String {
+    let combine = if statistic == "Maximum" { "MAX" } else { "SUM" };
+    format!(
+        "{}(SEARCH('{{{},QueryState,QueryType,WorkGroup}} MetricName=\"{}\" WorkGroup=\"{}\"', '{}', {}))",
+        combine, NAMESPACE, metric, workgroup, statistic, period
+    )
+}
+
+/// Totals and extremes of the fetched series
+#[derive(Debug, Default, PartialEq)]
+struct MetricsSummary {
+    queries: u64,
+    processed_bytes: i64,
+    avg_execution_ms: Option<i64>,
+    max_execution_ms: Option<i64>,
+    avg_queue_ms: Option<i64>,
+    max_queue_ms: Option<i64>,
+}
+
+impl MetricsSummary {
+    fn from_values(values: &HashMap<String, Vec<f64>>) -> Self {
+        let total = |id: &str| values.get(id).map_or(0.0, |v| v.iter().sum::<f64>());
+        let max = |id: &str| {
+            values
+                .get(id)
+                .and_then(|v| v.iter().copied().reduce(f64::max))
+                .map(|ms| ms.round() as i64)
+        };
+        let average = |sum: &str, count: &str| {
+            let count = total(count);
+            (count > 0.0).then(|| (total(sum) / count).round() as i64)
+        };
+
+        Self {
+            queries: total("queries").round() as u64,
+            processed_bytes: total("processed_bytes").round() as i64,
+            avg_execution_ms: average("execution_sum", "queries"),
+            max_execution_ms: max("execution_max"),
+            avg_queue_ms: average("queue_sum", "queue_count"),
+            max_queue_ms: max("queue_max"),
+        }
+    }
+
+    fn table(&self) -> Table {
+        let duration =
+            |ms: Option<i64>| ms.map_or_else(|| "-".to_string(), |ms| ms.format_duration_ms());
+
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![
+            Cell::new("Metric").style_spec("Fb"),
+            Cell::new("Value").style_spec("Fb"),
+        ]));
+        for (label, value) in [
+            ("Queries", self.queries.to_string()),
+            ("Data Processed", self.processed_bytes.format_bytes()),
+            ("Avg Total Execution Time", duration(self.avg_execution_ms)),
+            ("Max Total Execution Time", duration(self.max_execution_ms)),
+            ("Avg Queue Time", duration(self.avg_queue_ms)),
+            ("Max Queue Time", duration(self.max_queue_ms)),
+        ] {
+            table.add_row(Row::new(vec![Cell::new(label), Cell::new(&value)]));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_expression() {
+        assert_eq!(
+            search_expression("analytics", "ProcessedBytes", "Sum", 300),
+            "SUM(SEARCH('{AWS/Athena,QueryState,QueryType,WorkGroup} \
+             MetricName=\"ProcessedBytes\" WorkGroup=\"analytics\"', 'Sum', 300))"
+        );
+        assert!(search_expression("primary", "QueryQueueTime", "Maximum", 60).starts_with("MAX("));
+    }
+
+    #[test]
+    fn test_period_seconds() {
+        assert_eq!(period_seconds(Duration::from_secs(300)).unwrap(), 300);
+        assert!(period_seconds(Duration::from_secs(90)).is_err());
+        assert!(period_seconds(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_summary_from_values() {
+        let values: HashMap<String, Vec<f64>> = [
+            ("queries", vec![2.0, 1.0]),
+            ("processed_bytes", vec![1000.0, 500.0]),
+            ("execution_sum", vec![4000.0, 2000.0]),
+            ("execution_max", vec![3000.0, 2000.0]),
+            ("queue_count", vec![3.0]),
+            ("queue_sum", vec![300.0]),
+        ]
+        .into_iter()
+        .map(|(id, v)| (id.to_string(), v))
+        .collect();
+
+        assert_eq!(
+            MetricsSummary::from_values(&values),
+            MetricsSummary {
+                queries: 3,
+                processed_bytes: 1500,
+                avg_execution_ms: Some(2000),
+                max_execution_ms: Some(3000),
+                avg_queue_ms: Some(100),
+                max_queue_ms: None,
+            }
+        );
+    }
+}
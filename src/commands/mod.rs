@@ -1,6 +1,19 @@
+pub mod cancel;
+pub mod cleanup;
 pub mod common;
+pub mod completion;
+pub mod config;
 pub mod database;
+pub mod explain;
+pub mod export;
 pub mod history;
 pub mod inspect;
+pub mod library;
+pub mod metrics;
+pub mod named_query;
+pub mod prepared_statement;
 pub mod query;
+pub mod repl;
+pub mod rerun;
+pub mod whoami;
 pub mod workgroup;
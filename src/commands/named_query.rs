@@ -0,0 +1,187 @@
+//! Saved (named) queries stored in Athena, shared by everyone in a workgroup.
+
+use crate::cli::{CreateNamedQueryArgs, NamedQueryIdArgs};
+use crate::context::Context;
+use crate::utils::table_style;
+use crate::validation;
+use anyhow::Result;
+use aws_sdk_athena::types::NamedQuery;
+use aws_sdk_athena::Client;
+use colored::Colorize;
+use prettytable::{Cell, Row, Table};
+
+/// Maximum number of IDs accepted by `batch_get_named_query`
+const BATCH_SIZE: usize = 50;
+
+/// Lists the named queries in the current workgroup, limited to the current
+/// database when one is set
+pub async fn list(ctx: &Context) -> Result<()> {
+    let client = ctx.create_athena_client();
+    let workgroup = ctx.workgroup();
+    let database = ctx.database();
+
+    let named_queries: Vec<NamedQuery> = fetch_named_queries(&client, &workgroup)
+        .await?
+        .into_iter()
+        .filter(|query| match database.as_deref() {
+            Some(db) => query.database() == db,
+            None => true,
+        })
+        .collect();
+
+    if named_queries.is_empty() {
+        println!("No named queries found in workgroup: {}", workgroup);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Name").style_spec("Fb"),
+        Cell::new("Description").style_spec("Fb"),
+        Cell::new("Database").style_spec("Fb"),
+        Cell::new("ID").style_spec("Fb"),
+    ]));
+    for query in &named_queries {
+        table.add_row(Row::new(vec![
+            Cell::new(query.name()),
+            Cell::new(query.description().unwrap_or("-")),
+            Cell::new(query.database()),
+            Cell::new(query.named_query_id().unwrap_or("-")),
+        ]));
+    }
+
+    table_style::print(&table);
+    Ok(())
+}
+
+/// Prints a named query's details and SQL
+pub async fn show(ctx: &Context, args: &NamedQueryIdArgs) -> Result<()> {
+    let client = ctx.create_athena_client();
+
+    let result = client
+        .get_named_query()
+        .named_query_id(&args.id)
+        .send()
+        .await?;
+    let query = result
+        .named_query()
+        .ok_or_else(|| anyhow::anyhow!("No named query found with ID: {}", args.id))?;
+
+    println!("{}: {}", "Name".bold(), query.name());
+    if let Some(description) = query.description() {
+        println!("{}: {}", "Description".bold(), description);
+    }
+    println!("{}: {}", "Database".bold(), query.database());
+    if let Some(workgroup) = query.work_group() {
+        println!("{}: {}", "Workgroup".bold(), workgroup);
+    }
+    println!("\n{}", query.query_string());
+
+    Ok(())
+}
+
+/// Saves a query in the current workgroup after checking its syntax
+pub async fn create(ctx: &Context, args: &CreateNamedQueryArgs) -> Result<()> {
+    validation::validate_query_syntax(&args.query)?;
+
+    let database = args
+        .db
+        .clone()
+        .or_else(|| ctx.database())
+        .ok_or_else(|| anyhow::anyhow!("Database name is required but was not provided"))?;
+    let client = ctx.create_athena_client();
+
+    let result = client
+        .create_named_query()
+        .name(&args.name)
+        .set_description(args.description.clone())
+        .database(&database)
+        .query_string(&args.query)
+        .work_group(ctx.workgroup())
+        .send()
+        .await?;
+
+    println!(
+        "Created named query {} with ID: {}",
+        args.name.bright_green(),
+        result.named_query_id().unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Removes a named query by ID
+pub async fn delete(ctx: &Context, args: &NamedQueryIdArgs) -> Result<()> {
+    let client = ctx.create_athena_client();
+
+    client
+        .delete_named_query()
+        .named_query_id(&args.id)
+        .send()
+        .await?;
+
+    println!("Deleted named query: {}", args.id);
+    Ok(())
+}
+
+/// Fetches every named query in a workgroup
+pub async fn fetch_named_queries(client: &Client, workgroup: &str) -> Result<Vec<NamedQuery>> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let result = client
+            .list_named_queries()
+            .work_group(workgroup)
+            .set_next_token(next_token)
+            .send()
+            .await?;
+
+        ids.extend(result.named_query_ids().iter().cloned());
+
+        next_token = result.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    let mut named_queries = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(BATCH_SIZE) {
+        let result = client
+            .batch_get_named_query()
+            .set_named_query_ids(Some(chunk.to_vec()))
+            .send()
+            .await?;
+        named_queries.extend(result.named_queries().iter().cloned());
+    }
+
+    Ok(named_queries)
+}
+
+/// Finds the single named query called `name` in a workgroup
+pub async fn find_by_name(client: &Client, workgroup: &str, name: &str) -> Result<NamedQuery> {
+    let mut matches: Vec<NamedQuery> = fetch_named_queries(client, workgroup)
+        .await?
+        .into_iter()
+        .filter(|query| query.name() == name)
+        .collect();
+
+    match matches.len() {
+        0 => Err(anyhow::anyhow!(
+            "No named query called '{}' in workgroup: {}",
+            name,
+            workgroup
+        )),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let ids: Vec<&str> = matches
+                .iter()
+                .map(|query| query.named_query_id().unwrap_or("-"))
+                .collect();
+            Err(anyhow::anyhow!(
+                "Several named queries are called '{}', run one by ID instead: {}",
+                name,
+                ids.join(", ")
+            ))
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Server-side prepared statements, run with `query --prepared <name> --use <value>`.
+//!
+//! Unlike `--param`, which rewrites the query text, Athena binds the `USING`
+//! values to the statement's `?` parameters itself.
+
+use crate::cli::{PrepareArgs, PreparedStatementArgs};
+use crate::commands::common::OptionDateTimeFormat;
+use crate::context::Context;
+use crate::utils::table_style;
+use crate::validation;
+use anyhow::Result;
+use colored::Colorize;
+use prettytable::{Cell, Row, Table};
+
+/// Creates a prepared statement in the current workgroup
+pub async fn prepare(ctx: &Context, args: &PrepareArgs) -> Result<()> {
+    validation::validate_query_syntax(&args.sql)?;
+
+    let client = ctx.create_athena_client();
+    client
+        .create_prepared_statement()
+        .statement_name(&args.name)
+        .work_group(ctx.workgroup())
+        .query_statement(&args.sql)
+        .set_description(args.description.clone())
+        .send()
+        .await?;
+
+    println!(
+        "Prepared statement {} in workgroup: {}",
+        args.name.bright_green(),
+        ctx.workgroup()
+    );
+    Ok(())
+}
+
+/// Lists the prepared statements in the current workgroup
+pub async fn list(ctx: &Context) -> Result<()> {
+    let client = ctx.create_athena_client();
+    let workgroup = ctx.workgroup();
+    let timestamps = ctx.timestamp_format();
+
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Name").style_spec("Fb"),
+        Cell::new("Last Modified").style_spec("Fb"),
+    ]));
+
+    let mut count = 0;
+    let mut next_token: Option<String> = None;
+    loop {
+        let result = client
+            .list_prepared_statements()
+            .work_group(&workgroup)
+            .set_next_token(next_token)
+            .send()
+            .await?;
+
+        for statement in result.prepared_statements() {
+            count += 1;
+            table.add_row(Row::new(vec![
+                Cell::new(statement.statement_name().unwrap_or("-")),
+                Cell::new(
+                    &statement
+                        .last_modified_time()
+                        .format_datetime_or_default(&timestamps),
+                ),
+            ]));
+        }
+
+        next_token = result.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    if count == 0 {
+        println!("No prepared statements found in workgroup: {}", workgroup);
+        return Ok(());
+    }
+
+    table_style::print(&table);
+    Ok(())
+}
+
+/// Removes a prepared statement from the current workgroup
+pub async fn delete(ctx: &Context, args: &PreparedStatementArgs) -> Result<()> {
+    let client = ctx.create_athena_client();
+
+    client
+        .delete_prepared_statement()
+        .statement_name(&args.name)
+        .work_group(ctx.workgroup())
+        .send()
+        .await?;
+
+    println!("Deleted prepared statement: {}", args.name);
+    Ok(())
+}
+
+/// Builds the `EXECUTE` statement that runs `name` with the given parameter values
+pub fn execute_statement(name: &str, values: &[String]) -> String {
+    if values.is_empty() {
+        format!("EXECUTE {}", name)
+    } else {
+        format!("EXECUTE {} USING {}", name, values.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute_statement() {
+        assert_eq!(execute_statement("daily", &[]), "EXECUTE daily");
+        assert_eq!(
+            execute_statement("daily", &["'2024-01-01'".to_string(), "5".to_string()]),
+            "EXECUTE daily USING '2024-01-01', 5"
+        );
+    }
+}
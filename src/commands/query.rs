@@ -26,25 +26,91 @@
 //! athena-cli query --reuse-time 2h "SELECT * FROM my_table"
 //! ```
 //!
+//! Stream rows as newline-delimited JSON while they are fetched:
+//!
+//! ```bash
+//! athena-cli query --format ndjson "SELECT * FROM my_table" | jq .
+//! ```
+//!
+//! Save the results to a Parquet file:
+//!
+//! ```bash
+//! athena-cli query -o results.parquet "SELECT * FROM my_table"
+//! ```
+//!
+//! Fill in placeholders client-side before the query is validated and sent:
+//!
+//! ```bash
+//! athena-cli query --param date=2024-01-01 "SELECT * FROM my_table WHERE dt = ':date'"
+//! ```
+//!
+//! Run a prepared statement, binding its '?' parameters server-side:
+//!
+//! ```bash
+//! athena-cli query --prepared daily_orders --use "'2024-01-01'" --use 5
+//! ```
+//!
+//! Run a saved query from the current workgroup:
+//!
+//! ```bash
+//! athena-cli query --named daily_orders -o daily.csv
+//! ```
+//!
+//! Run `report_x.sql` from the local query library (`app.query_library_dir`):
+//!
+//! ```bash
+//! athena-cli query --name report_x --param date=2024-01-01
+//! ```
+//!
+//! Label a query for cost allocation, then find its executions in the history:
+//!
+//! ```bash
+//! athena-cli query --label team=analytics "SELECT * FROM my_table"
+//! athena-cli history --grep tag:team=analytics
+//! ```
+//!
+//! Run every statement in a file, two at a time:
+//!
+//! ```bash
+//! athena-cli query --batch reports.sql --parallel 2
+//! ```
+//!
 //! Query with output location:
 //!
 //! ```bash
 //! athena-cli --output-location s3://my-bucket/results/ query "SELECT * FROM my_table"
 //! ```
 
+use crate::aws::s3::{expand_output_location, validate_output_location, OutputLocationValues};
 use crate::cli;
+use crate::commands::common::{
+    execution_cost, format_cost, table_cell, truncate_value, used_result_cache, ByteDisplay,
+    DurationFormat, OptionByteDisplay, ResultReuse,
+};
+use crate::commands::inspect::fields::{get_field_value, InspectField};
+use crate::commands::{database, export, library, named_query, prepared_statement};
+use crate::config::OutputFormat;
 use crate::context::Context;
+use crate::error::AthenaCliError;
+use crate::local_history::{HistoryEntry, LocalHistory};
+use crate::utils::labels::prepend_labels;
+use crate::utils::params::substitute_params;
+use crate::utils::suggest::closest_matches;
+use crate::utils::{pager, table_style};
 use crate::validation;
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use aws_sdk_athena::types::{
-    QueryExecutionContext, QueryExecutionState, ResultConfiguration, ResultReuseByAgeConfiguration,
-    ResultReuseConfiguration,
+    QueryExecution, QueryExecutionContext, QueryExecutionState, ResultConfiguration,
+    ResultReuseByAgeConfiguration, ResultReuseConfiguration, Row,
 };
 use aws_sdk_athena::Client;
-use byte_unit::Byte;
+use chrono::Utc;
 use colored::Colorize;
+use futures::stream::{self, StreamExt};
 use polars::prelude::*;
-use std::{thread, time::Duration};
+use std::env;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
 
 /// Executes an Athena SQL query and displays the results.
 ///
@@ -90,41 +156,355 @@ use std::{thread, time::Duration};
 /// athena-cli -w my_workgroup --output-location s3://my-bucket/results/ query "SELECT * FROM my_table"
 /// ```
 pub async fn execute(ctx: &Context, args: &cli::QueryArgs) -> Result<()> {
-    println!("Executing query: {}", args.query);
+    // Progress and status messages go to stderr so stdout only carries results,
+    // and quiet mode drops them entirely
+    let destination = args.output.as_ref().or(args.tee.as_ref());
+    let format = result_format(
+        ctx.query_format(args.format.map(OutputFormat::from)),
+        args.format,
+        destination.map(String::as_str),
+    );
+    let json_summary = prints_json_summary(format, args.output.as_deref());
+    let status = |message: String| {
+        if !ctx.quiet() {
+            eprintln!("{}", message);
+        }
+    };
+
+    let client = ctx.create_athena_client();
+
+    if let Some(path) = &args.batch {
+        return run_batch(ctx, &client, args, path).await;
+    }
+
+    // A saved query brings its own SQL and the database it was saved for.
+    // Prepared statements were validated when they were created, and sqlparser
+    // doesn't understand EXECUTE ... USING, so those skip validation.
+    let (query, database, validate) = match (&args.named, &args.prepared, &args.name) {
+        (Some(name), _, _) => {
+            let named = named_query::find_by_name(&client, &ctx.workgroup(), name).await?;
+            (
+                named.query_string().to_string(),
+                Some(named.database().to_string()),
+                true,
+            )
+        }
+        (None, Some(name), _) => (
+            prepared_statement::execute_statement(name, &args.using),
+            ctx.database(),
+            false,
+        ),
+        (None, None, Some(name)) => (
+            library::load(ctx, args.sql_file_dir.as_deref(), name)?,
+            ctx.database(),
+            true,
+        ),
+        (None, None, None) => (args.query.clone().unwrap_or_default(), ctx.database(), true),
+    };
+    let query =
+        substitute_params(&query, &args.params, args.strict_params).map_err(anyhow::Error::msg)?;
+
+    // The validator parses ANSI SQL and can reject valid Athena syntax, so it
+    // can be skipped. The auto limit needs the parsed query, so it's skipped too.
+    let validate = validate && !args.no_validate;
+    if args.no_validate {
+        status(
+            "Skipping client-side SQL validation (--no-validate)"
+                .yellow()
+                .to_string(),
+        );
+    }
 
     // Validate SQL syntax before sending to Athena
-    if let Err(e) = validation::validate_query_syntax(&args.query) {
-        println!("{}", "SQL syntax validation failed".red().bold());
-        return Err(e);
+    if validate {
+        if let Err(e) = validation::validate_query_syntax(&query) {
+            eprintln!("{}", "SQL syntax validation failed".red().bold());
+            return Err(e);
+        }
     }
 
-    let database = ctx
-        .database()
+    // Guard against accidentally fetching a whole table. Exports with
+    // --unload-to are meant to be complete, so they are left alone, and a
+    // LIMIT inside a --count-only query would change the count.
+    let unloading = args.unload_to.is_some();
+    let query = match ctx
+        .auto_limit()
+        .filter(|_| validate && !args.no_limit && !unloading && !args.count_only)
+    {
+        Some(limit) => match validation::apply_row_limit(&query, limit)? {
+            Some(limited) => {
+                status(
+                    format!("Added LIMIT {} (use --no-limit to fetch all rows)", limit)
+                        .yellow()
+                        .to_string(),
+                );
+                limited
+            }
+            None => query,
+        },
+        None => query,
+    };
+    let query = if args.count_only {
+        validation::count_rows_query(&query)?
+    } else {
+        query
+    };
+    let query = match &args.unload_to {
+        Some(location) => {
+            validate_output_location(location)?;
+            unload_statement(&query, location, args.unload_format)
+        }
+        None => query,
+    };
+    let query = prepend_labels(&query, &args.labels);
+    let query = query.as_str();
+
+    status(format!("Executing query: {}", query));
+
+    let database = database
         .ok_or_else(|| anyhow::anyhow!("Database name is required but was not provided"))?;
 
-    let client = ctx.create_athena_client();
+    if args.check_tables {
+        check_tables_exist(&client, &ctx.catalog(), &database, query).await?;
+    }
+
+    if let Some(runs) = args.repeat {
+        return repeat_query(ctx, &client, &database, query, runs).await;
+    }
 
+    let reuse_time = if args.no_reuse {
+        Duration::ZERO
+    } else {
+        args.reuse_time
+    };
     let query_id = start_query(
         &client,
         &database,
-        &args.query,
+        query,
         &ctx.workgroup(),
-        args.reuse_time,
-        ctx.output_location()
-            .as_deref()
-            .unwrap_or("s3://aws-athena-query-results"),
+        reuse_time,
+        ctx.output_location().as_deref(),
     )
     .await?;
 
-    println!("Query execution ID: {}", query_id);
+    status(format!("Query execution ID: {}", query_id));
 
-    let df = get_query_results(&client, &query_id).await?;
-    println!("Results DataFrame:");
-    println!("{}", df);
+    // Failed and cancelled runs are recorded too, since they're the ones most
+    // worth looking up later
+    let execution = wait_until_finished(&client, &query_id, None).await?;
+    record_history(ctx, query, &execution);
+    let execution = ensure_succeeded(execution)?;
 
+    if let Some(location) = &args.unload_to {
+        report_finished_execution(ctx, &client, &execution, args, None).await;
+        if ctx.quiet() {
+            println!("{}", location);
+        } else {
+            println!("Results unloaded to: {}", location.bright_green());
+        }
+        return Ok(());
+    }
+
+    if args.count_only {
+        report_finished_execution(ctx, &client, &execution, args, None).await;
+        let count = counted_rows(&client, &query_id).await?;
+        println!("{}", count);
+        return check_row_count(args, count);
+    }
+
+    // CTAS and UNLOAD write their rows elsewhere and return none, so what they
+    // wrote is reported instead
+    if let Some(statement) = WriteStatement::detect(&execution, query) {
+        report_finished_execution(ctx, &client, &execution, args, None).await;
+        let rows = output_rows(&client, &query_id).await;
+        statement.report(rows, ctx.quiet());
+        // Row checks apply to the rows written
+        if args.fail_empty || args.assert_rows.is_some() {
+            let rows = rows
+                .and_then(|rows| u64::try_from(rows).ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Athena didn't report how many rows query {} wrote, so \
+                         --fail-empty and --assert-rows can't be checked",
+                        query_id
+                    )
+                })?;
+            return check_row_count(args, rows);
+        }
+        return Ok(());
+    }
+
+    // The row cap protects the terminal, so saved files always get every row,
+    // and so do --assert-rows checks, which count them
+    let max_rows = match args.max_rows.unwrap_or_else(|| ctx.max_rows()) {
+        0 => None,
+        _ if destination.is_some() || args.assert_rows.is_some() => None,
+        max_rows => Some(max_rows),
+    };
+
+    // ndjson without a file streams rows, the same as --follow=ndjson
+    let follow = match (format, destination) {
+        (Some(cli::ResultFormat::Ndjson), None) => Some(cli::FollowFormat::Ndjson),
+        _ => args.follow,
+    };
+
+    if let Some(format) = follow {
+        if !args.select.is_empty() || !args.where_contains.is_empty() {
+            return Err(AthenaCliError::Validation(
+                "--select and --where-contains can't be used when rows are streamed \
+                 (--format ndjson without --output)"
+                    .to_string(),
+            )
+            .into());
+        }
+        let rows =
+            follow_query_results(&client, &execution, format, ctx.verbose(), max_rows).await?;
+        report_finished_execution(ctx, &client, &execution, args, None).await;
+        return check_row_count(args, rows as u64);
+    }
+
+    let (mut df, fetched) = get_query_results(
+        &client,
+        &query_id,
+        ctx.verbose(),
+        max_rows,
+        &args.where_contains,
+        &args.select,
+    )
+    .await?;
+    report_finished_execution(ctx, &client, &execution, args, Some(df.height())).await;
+    if fetched.truncated {
+        status(
+            omitted_rows_notice(&client, &query_id, fetched.count)
+                .await
+                .yellow()
+                .to_string(),
+        );
+    }
+    // Counted now, since showing the results consumes the DataFrame
+    let rows = df.height() as u64;
+
+    let saved_path = match destination {
+        Some(output) => Some(export::save_dataframe(
+            &mut df,
+            output,
+            &query_id,
+            format,
+            &export::CsvOptions {
+                delimiter: args.delimiter,
+                bom: args.bom,
+                quote_style: args.quote_style,
+            },
+        )?),
+        None => None,
+    };
+    let output_location = execution
+        .result_configuration()
+        .and_then(|c| c.output_location())
+        .unwrap_or_default();
+
+    if json_summary {
+        println!(
+            "{}",
+            serde_json::json!({
+                "query_execution_id": query_id,
+                "output_location": output_location,
+                "output_file": saved_path.as_ref().map(|p| p.display().to_string()),
+            })
+        );
+    } else if ctx.quiet() {
+        // One value per line so wrapper scripts can read them directly
+        println!("{}", query_id);
+        println!("{}", output_location);
+        if let Some(path) = &saved_path {
+            println!("{}", path.display());
+        }
+    } else {
+        if let Some(path) = &saved_path {
+            eprintln!(
+                "Results saved to: {}",
+                path.display().to_string().bright_green()
+            );
+        }
+        // --output replaces the table, --tee prints it as well
+        if format == Some(cli::ResultFormat::Json) && destination.is_none() {
+            export::write_dataframe(
+                &mut df,
+                cli::ResultFormat::Json,
+                &export::CsvOptions::default(),
+                io::stdout().lock(),
+            )?;
+            println!();
+        } else if args.output.is_none() {
+            eprintln!("Results DataFrame:");
+            let shown = match args.head.filter(|&head| head < df.height()) {
+                Some(head) => {
+                    status(
+                        format!(
+                            "Showing the first {} of {} rows (--head)",
+                            head,
+                            df.height()
+                        )
+                        .yellow()
+                        .to_string(),
+                    );
+                    df.head(Some(head))
+                }
+                None => df,
+            };
+            let shown = truncate_columns(&shown, ctx.max_column_width())?;
+            let vertical = args.vertical || too_wide_for_terminal(&shown);
+            pager::page(&render_results(&shown, vertical))?;
+        }
+    }
+
+    check_row_count(args, rows)
+}
+
+/// Fails with an assertion error when the number of rows the query returned
+/// doesn't meet `--fail-empty` or `--assert-rows`
+fn check_row_count(args: &cli::QueryArgs, rows: u64) -> Result<()> {
+    if args.fail_empty && rows == 0 {
+        return Err(AthenaCliError::AssertionFailed(
+            "Query returned no rows (--fail-empty)".to_string(),
+        )
+        .into());
+    }
+    if let Some(assertion) = args.assert_rows.filter(|assertion| !assertion.holds(rows)) {
+        return Err(AthenaCliError::AssertionFailed(format!(
+            "Query returned {} rows, expected {} (--assert-rows)",
+            rows, assertion
+        ))
+        .into());
+    }
     Ok(())
 }
 
+/// The format of the results: the `flag` (--format), else the `configured`
+/// format resolved from it. A configured format gives way to the extension of
+/// the file the results are saved to, and formats that need a file only apply
+/// when the results are saved. `None` prints a table.
+fn result_format(
+    configured: OutputFormat,
+    flag: Option<cli::ResultFormat>,
+    destination: Option<&str>,
+) -> Option<cli::ResultFormat> {
+    let format = cli::ResultFormat::from_output_format(configured)?;
+    if flag.is_some() {
+        return Some(format);
+    }
+    match destination {
+        Some(output) => (!export::has_format_extension(output)).then_some(format),
+        None => (!format.needs_file()).then_some(format),
+    }
+}
+
+/// Whether the JSON summary of where the results went is printed instead of the
+/// rows. It describes saved results, so without --output the rows are printed.
+fn prints_json_summary(format: Option<cli::ResultFormat>, output: Option<&str>) -> bool {
+    format == Some(cli::ResultFormat::Json) && output.is_some()
+}
+
 /// Starts an Athena query execution with the specified parameters and returns the execution ID.
 ///
 /// # Arguments
@@ -134,7 +514,8 @@ pub async fn execute(ctx: &Context, args: &cli::QueryArgs) -> Result<()> {
 /// * `query` - The SQL query string to execute
 /// * `workgroup` - The Athena workgroup to use
 /// * `reuse_duration` - Duration for which query results should be reused/cached
-/// * `output_location` - S3 location where query results will be stored
+/// * `output_location` - S3 location where query results will be stored, or `None`
+///   to use the workgroup's configured location
 ///
 /// # Returns
 ///
@@ -142,31 +523,67 @@ pub async fn execute(ctx: &Context, args: &cli::QueryArgs) -> Result<()> {
 ///
 /// # Implementation Details
 ///
+/// * Rejects queries over Athena's length limit before sending them
+/// * Fills in `{date}`, `{workgroup}`, `{database}` and `{user}` placeholders in
+///   the output location, then checks it is a well-formed `s3://` URL
 /// * Configures the query context with database and output location
 /// * Sets up result reuse configuration based on the provided duration
+///   (a zero duration disables reuse so the query always runs fresh)
 /// * Returns the execution ID that can be used to track and retrieve results
-async fn start_query(
+pub async fn start_query(
     client: &Client,
     database: &str,
     query: &str,
     workgroup: &str,
     reuse_duration: Duration,
-    output_location: &str,
+    output_location: Option<&str>,
 ) -> Result<String> {
+    validation::validate_query_length(query)?;
+
+    let output_location = output_location
+        .map(|template| {
+            let user = env::var("USER").or_else(|_| env::var("USERNAME")).ok();
+            expand_output_location(
+                template,
+                &OutputLocationValues {
+                    date: Utc::now().date_naive(),
+                    workgroup,
+                    database,
+                    user: user.as_deref(),
+                },
+            )
+        })
+        .transpose()?;
+    if let Some(location) = &output_location {
+        validate_output_location(location)?;
+    }
+
     let context = QueryExecutionContext::builder().database(database).build();
 
     let config = ResultConfiguration::builder()
-        .output_location(output_location)
+        .set_output_location(output_location)
         .build();
 
+    let max_age = reuse_max_age_minutes(reuse_duration);
+    if max_age == Some(0) {
+        eprintln!(
+            "{}",
+            format!(
+                "Warning: reuse time {} is under a minute and rounds down to 0 minutes, so results won't be reused",
+                humantime::format_duration(reuse_duration)
+            )
+            .yellow()
+        );
+    }
+
     let result = client
         .start_query_execution()
         .result_reuse_configuration(
             ResultReuseConfiguration::builder()
                 .result_reuse_by_age_configuration(
                     ResultReuseByAgeConfiguration::builder()
-                        .enabled(true)
-                        .max_age_in_minutes(reuse_duration.as_secs() as i32 / 60)
+                        .enabled(max_age.is_some())
+                        .set_max_age_in_minutes(max_age)
                         .build(),
                 )
                 .build(),
@@ -176,95 +593,982 @@ async fn start_query(
         .result_configuration(config)
         .work_group(workgroup)
         .send()
-        .await?;
+        .await
+        .map_err(AthenaCliError::from_sdk)?;
 
     Ok(result.query_execution_id().unwrap_or_default().to_string())
 }
 
-/// Retrieves query results and converts them to a Polars DataFrame.
+/// Runs each statement of a `--batch` script as its own execution, at most
+/// `--parallel` at a time, then prints how each one ended. Unless
+/// `--no-validate` is given, every statement is checked before any of them
+/// starts. The auto limit isn't applied, since no results are fetched.
+async fn run_batch(
+    ctx: &Context,
+    client: &Client,
+    args: &cli::QueryArgs,
+    path: &str,
+) -> Result<()> {
+    let script =
+        std::fs::read_to_string(path).context(format!("Failed to read batch file: {}", path))?;
+    let statements = validation::split_statements(&script)?
+        .iter()
+        .enumerate()
+        .map(|(index, statement)| {
+            let statement = substitute_params(statement, &args.params, args.strict_params)
+                .map_err(anyhow::Error::msg)?;
+            if !args.no_validate {
+                validation::validate_query_syntax(&statement)
+                    .with_context(|| format!("Statement {} of {} is invalid", index + 1, path))?;
+            }
+            Ok(statement)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if statements.is_empty() {
+        return Err(AthenaCliError::Validation(format!("No statements found in {}", path)).into());
+    }
+    if args.no_validate && !ctx.quiet() {
+        eprintln!(
+            "{}",
+            "Skipping client-side SQL validation (--no-validate)".yellow()
+        );
+    }
+
+    let database = ctx
+        .database()
+        .ok_or_else(|| anyhow::anyhow!("Database name is required but was not provided"))?;
+    let workgroup = ctx.workgroup();
+    let output_location = ctx.output_location();
+    let reuse_time = if args.no_reuse {
+        Duration::ZERO
+    } else {
+        args.reuse_time
+    };
+    let parallel = args.parallel.unwrap_or(1);
+
+    if !ctx.quiet() {
+        eprintln!(
+            "Running {} statements from {}, {} at a time",
+            statements.len(),
+            path,
+            parallel
+        );
+    }
+
+    let (database, workgroup, output_location) =
+        (&database, &workgroup, output_location.as_deref());
+    // Results come back in statement order, whichever finishes first
+    let results: Vec<Result<QueryExecution>> = stream::iter(&statements)
+        .map(|statement| async move {
+            let statement = prepend_labels(statement, &args.labels);
+            let query_id = start_query(
+                client,
+                database,
+                &statement,
+                workgroup,
+                reuse_time,
+                output_location,
+            )
+            .await?;
+            let execution = wait_until_finished(client, &query_id, None).await?;
+            record_history(ctx, &statement, &execution);
+            Ok::<_, anyhow::Error>(execution)
+        })
+        .buffered(parallel as usize)
+        .collect()
+        .await;
+
+    let mut table = prettytable::Table::new();
+    table.add_row(prettytable::row![
+        Fb->"#",
+        Fb->"Query ID",
+        Fb->"Status",
+        Fb->"Data Scanned",
+        Fb->"Query"
+    ]);
+    let mut failures = Vec::new();
+    for (index, (statement, result)) in statements.iter().zip(&results).enumerate() {
+        let number = index + 1;
+        let (query_id, state, scanned) = match result {
+            Ok(execution) => {
+                let status = execution.status();
+                let state = status.and_then(|s| s.state());
+                if !matches!(state, Some(QueryExecutionState::Succeeded)) {
+                    failures.push(format!(
+                        "Statement {}: {}",
+                        number,
+                        status
+                            .and_then(|s| s.state_change_reason())
+                            .unwrap_or("failed or was cancelled")
+                    ));
+                }
+                (
+                    execution.query_execution_id().unwrap_or("-").to_string(),
+                    state.map_or("UNKNOWN", |s| s.as_str()).to_string(),
+                    execution
+                        .statistics()
+                        .and_then(|s| s.data_scanned_in_bytes())
+                        .format_bytes_or_default(),
+                )
+            }
+            Err(err) => {
+                failures.push(format!("Statement {}: {:#}", number, err));
+                ("-".to_string(), "ERROR".to_string(), "-".to_string())
+            }
+        };
+
+        // Keep each statement on one line of the table
+        let one_line = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+        table.add_row(prettytable::Row::new(vec![
+            prettytable::Cell::new(&number.to_string()),
+            prettytable::Cell::new(&query_id),
+            prettytable::Cell::new(&state),
+            prettytable::Cell::new(&scanned),
+            table_cell(&one_line, ctx.max_column_width()),
+        ]));
+    }
+    pager::page_table(&table)?;
+
+    for failure in &failures {
+        eprintln!("{}", failure.red());
+    }
+    if !failures.is_empty() {
+        return Err(AthenaCliError::QueryFailed(format!(
+            "{} of {} statements failed",
+            failures.len(),
+            statements.len()
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Runs the query `runs` times one after another with result reuse disabled,
+/// reporting each run's engine time and data scanned, then prints their
+/// min/median/max. Handy for checking that a change actually prunes partitions.
+async fn repeat_query(
+    ctx: &Context,
+    client: &Client,
+    database: &str,
+    query: &str,
+    runs: u32,
+) -> Result<()> {
+    let mut engine_times = Vec::new();
+    let mut data_scanned = Vec::new();
+
+    for run in 1..=runs {
+        let query_id = start_query(
+            client,
+            database,
+            query,
+            &ctx.workgroup(),
+            Duration::ZERO,
+            ctx.output_location().as_deref(),
+        )
+        .await?;
+        let execution = wait_until_finished(client, &query_id, None).await?;
+        record_history(ctx, query, &execution);
+        let execution = ensure_succeeded(execution)?;
+
+        let statistics = execution.statistics();
+        let engine_time = statistics
+            .and_then(|s| s.engine_execution_time_in_millis())
+            .unwrap_or(0);
+        let scanned = statistics
+            .and_then(|s| s.data_scanned_in_bytes())
+            .unwrap_or(0);
+        if !ctx.quiet() {
+            eprintln!(
+                "Run {}/{} ({}): {}, scanned {}",
+                run,
+                runs,
+                query_id,
+                engine_time.format_duration_ms(),
+                scanned.format_bytes()
+            );
+        }
+        engine_times.push(engine_time);
+        data_scanned.push(scanned);
+    }
+
+    let mut table = prettytable::Table::new();
+    table.add_row(prettytable::row![Fb->"", Fb->"Min", Fb->"Median", Fb->"Max"]);
+    if let Some((min, median, max)) = min_median_max(&engine_times) {
+        table.add_row(prettytable::row![
+            b->"Engine time",
+            min.format_duration_ms(),
+            median.format_duration_ms(),
+            max.format_duration_ms()
+        ]);
+    }
+    if let Some((min, median, max)) = min_median_max(&data_scanned) {
+        table.add_row(prettytable::row![
+            b->"Data scanned",
+            min.format_bytes(),
+            median.format_bytes(),
+            max.format_bytes()
+        ]);
+    }
+    table_style::print(&table);
+
+    Ok(())
+}
+
+/// Smallest, median and largest of `values`, or `None` when there are none.
+/// The median of an even number of values is the mean of the middle two.
+fn min_median_max(values: &[i64]) -> Option<(i64, i64, i64)> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+
+    let (&min, &max) = (sorted.first()?, sorted.last()?);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2
+    } else {
+        sorted[middle]
+    };
+    Some((min, median, max))
+}
+
+/// Verifies that every table the query reads exists, so a typo fails fast
+/// instead of after a round-trip to Athena. Missing tables are reported
+/// together, each with the closest table names in its database.
+async fn check_tables_exist(
+    client: &Client,
+    catalog: &str,
+    database: &str,
+    query: &str,
+) -> Result<()> {
+    let mut missing = Vec::new();
+
+    for parts in validation::referenced_tables(query)? {
+        let (table_catalog, table_database, table) = match parts.as_slice() {
+            [table] => (catalog, database, table),
+            [db, table] => (catalog, db.as_str(), table),
+            [cat, db, table] => (cat.as_str(), db.as_str(), table),
+            _ => continue,
+        };
+
+        let result = client
+            .get_table_metadata()
+            .catalog_name(table_catalog)
+            .database_name(table_database)
+            .table_name(table)
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(err) if database::is_table_not_found(&err) => {
+                // Suggestions are best-effort, a failed listing just means none
+                let names = database::table_names(client, table_catalog, table_database)
+                    .await
+                    .unwrap_or_default();
+                let suggestions = closest_matches(table, names.iter().map(String::as_str));
+
+                let mut line = format!("  {}.{}", table_database, table);
+                if !suggestions.is_empty() {
+                    line.push_str(&format!(" (did you mean: {}?)", suggestions.join(", ")));
+                }
+                missing.push(line);
+            }
+            Err(err) => {
+                return Err(anyhow::Error::new(err).context(format!(
+                    "Failed to check table {}.{}",
+                    table_database, table
+                )))
+            }
+        }
+    }
+
+    if !missing.is_empty() {
+        anyhow::bail!("Query references unknown tables:\n{}", missing.join("\n"));
+    }
+
+    Ok(())
+}
+
+/// Collects the first column of every result row of a finished query. Meant for
+/// statements whose output is a single text column, like `EXPLAIN` or
+/// `SHOW PARTITIONS`, where Athena may or may not include a header row.
+pub async fn first_column_values(client: &Client, query_execution_id: &str) -> Result<Vec<String>> {
+    let mut values = Vec::new();
+    let mut next_token: Option<String> = None;
+    let mut first_page = true;
+
+    loop {
+        let results = client
+            .get_query_results()
+            .query_execution_id(query_execution_id)
+            .set_next_token(next_token)
+            .send()
+            .await?;
+
+        if let Some(rs) = results.result_set() {
+            let header = rs
+                .result_set_metadata()
+                .and_then(|m| m.column_info().first())
+                .map(|c| c.name());
+
+            for (index, row) in rs.rows().iter().enumerate() {
+                let value = row
+                    .data()
+                    .first()
+                    .and_then(|d| d.var_char_value())
+                    .unwrap_or_default();
+
+                // Skip the header row if Athena included one
+                if first_page && index == 0 && Some(value) == header {
+                    continue;
+                }
+                values.push(value.to_string());
+            }
+        }
+        first_page = false;
+
+        next_token = results.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(values)
+}
+
+/// Appends a finished query to the local history log. Failing to write the log
+/// never fails the query itself.
+fn record_history(ctx: &Context, query: &str, execution: &QueryExecution) {
+    let entry = HistoryEntry {
+        query_execution_id: execution
+            .query_execution_id()
+            .unwrap_or_default()
+            .to_string(),
+        query: query.to_string(),
+        workgroup: execution
+            .work_group()
+            .map_or_else(|| ctx.workgroup(), str::to_string),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        state: execution
+            .status()
+            .and_then(|s| s.state())
+            .map_or("UNKNOWN", |s| s.as_str())
+            .to_string(),
+        data_scanned: execution
+            .statistics()
+            .and_then(|s| s.data_scanned_in_bytes()),
+    };
+
+    if let Err(e) = LocalHistory::open().and_then(|history| history.append(&entry)) {
+        if ctx.verbose() {
+            eprintln!("Failed to record query in local history: {}", e);
+        }
+    }
+}
+
+/// Athena's result reuse window for a `--reuse-time` duration, in whole minutes.
+/// A zero duration disables reuse (`None`); anything else is truncated to minutes.
+fn reuse_max_age_minutes(reuse_duration: Duration) -> Option<i32> {
+    if reuse_duration.is_zero() {
+        return None;
+    }
+
+    Some(i32::try_from(reuse_duration.as_secs() / 60).unwrap_or(i32::MAX))
+}
+
+/// Retrieves the results of a succeeded query and converts them to a Polars DataFrame.
 ///
 /// # Arguments
 ///
 /// * `client` - The AWS Athena SDK client
 /// * `query_execution_id` - The execution ID of the query whose results to retrieve
+/// * `verbose` - Whether to print per-page progress while paginating
+/// * `max_rows` - Stop fetching after this many rows, or `None` for all of them
+/// * `where_contains` - `(column, text)` pairs a row must all match to be kept
+/// * `select` - Columns to keep (from `--select`), or empty for all of them
 ///
 /// # Returns
 ///
-/// Returns a Result containing a Polars DataFrame with the query results
+/// Returns a Result containing a Polars DataFrame with the query results and
+/// how many rows were fetched
 ///
 /// # Behavior
 ///
-/// * Polls the query execution until it succeeds, fails, or is cancelled
-/// * Displays query statistics including data scanned and cache status
-/// * Paginates through results if they span multiple pages (100 rows per page)
+/// * Paginates through results if they span multiple pages (100 rows per page),
+///   stopping early once `max_rows` rows have been fetched
 /// * Converts query results to a Polars DataFrame for analysis and display
+/// * Filters the rows with `where_contains`, then projects the DataFrame down to
+///   the `select` columns, before it's displayed or saved
 ///
 /// # Error Handling
 ///
-/// * Returns an error if the query fails or is cancelled
 /// * Handles partial results and pagination automatically
-async fn get_query_results(client: &Client, query_execution_id: &str) -> Result<DataFrame> {
-    // Wait for query to complete
+async fn get_query_results(
+    client: &Client,
+    query_execution_id: &str,
+    verbose: bool,
+    max_rows: Option<usize>,
+    where_contains: &[(String, String)],
+    select: &[String],
+) -> Result<(DataFrame, FetchedRows)> {
+    let mut all_columns: Vec<Vec<String>> = Vec::new();
+    let mut column_names: Vec<String> = Vec::new();
+
+    let fetched = for_each_result_page(
+        client,
+        query_execution_id,
+        verbose,
+        max_rows,
+        |columns, rows| {
+            if column_names.is_empty() {
+                column_names = columns.to_vec();
+                all_columns = vec![Vec::new(); column_names.len()];
+            }
+
+            for row in rows {
+                for (i, data) in row.data().iter().enumerate() {
+                    all_columns[i].push(data.var_char_value().unwrap_or_default().to_string());
+                }
+            }
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Create DataFrame
+    let series = all_columns
+        .iter()
+        .zip(column_names.iter())
+        .map(|(col, name)| Series::new(name.into(), col))
+        .map(|s| s.into_column())
+        .collect();
+
+    // Convert Series to Columns and create DataFrame
+    let mut df = DataFrame::new(series)?;
+    if !where_contains.is_empty() {
+        df = filter_rows(df, where_contains)?;
+    }
+    if !select.is_empty() {
+        df = select_columns(&df, select)?;
+    }
+
+    Ok((df, fetched))
+}
+
+/// Shortens every string value in `df` to `max_width` characters with
+/// [`truncate_value`], 0 for no limit. Polars' own limit is lifted in `main`.
+fn truncate_columns(df: &DataFrame, max_width: usize) -> Result<DataFrame> {
+    if max_width == 0 {
+        return Ok(df.clone());
+    }
+
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|column| match column.str() {
+            Ok(values) => values
+                .into_iter()
+                .map(|value| value.map(|value| truncate_value(value, max_width)))
+                .collect::<StringChunked>()
+                .with_name(column.name().clone())
+                .into_column(),
+            Err(_) => column.clone(),
+        })
+        .collect();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Renders query results as a table, or with `vertical` as one `name: value`
+/// line per column for each row, like MySQL's `\G`
+fn render_results(df: &DataFrame, vertical: bool) -> String {
+    if !vertical {
+        return df.to_string();
+    }
+
+    let names = df.get_column_names_str();
+    let name_width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let separator = "*".repeat(27);
+
+    let mut rendered = String::new();
+    for row in 0..df.height() {
+        rendered.push_str(&format!("{} {}. row {}\n", separator, row + 1, separator));
+        for (name, column) in names.iter().zip(df.get_columns()) {
+            let value = column
+                .str()
+                .ok()
+                .and_then(|values| values.get(row))
+                .unwrap_or("NULL");
+            rendered.push_str(&format!(
+                "{:>width$}: {}\n",
+                name,
+                value,
+                width = name_width
+            ));
+        }
+    }
+    rendered
+}
+
+/// Whether the result is a single row whose table would be wider than the
+/// terminal. Always false when stdout isn't a terminal.
+fn too_wide_for_terminal(df: &DataFrame) -> bool {
+    let Some((_, terminal_width)) = console::Term::stdout().size_checked() else {
+        return false;
+    };
+    df.height() == 1 && table_width(df) > usize::from(terminal_width)
+}
+
+/// Approximate width of `df` drawn as a table: each column as wide as its name
+/// or value, plus padding and a border
+fn table_width(df: &DataFrame) -> usize {
+    df.get_columns()
+        .iter()
+        .map(|column| {
+            let values = column.str().ok();
+            let value_width = (0..df.height())
+                .filter_map(|row| values.and_then(|values| values.get(row)))
+                .map(|value| value.chars().count())
+                .max()
+                .unwrap_or(0);
+            column.name().chars().count().max(value_width) + 3
+        })
+        .sum::<usize>()
+        + 1
+}
+
+/// Keeps the rows where every filter's column contains its text, ignoring case.
+/// Fails on an unknown column name, listing the columns the result does have.
+fn filter_rows(df: DataFrame, filters: &[(String, String)]) -> Result<DataFrame> {
+    check_columns_exist(
+        &df,
+        filters.iter().map(|(column, _)| column),
+        "--where-contains",
+    )?;
+
+    let predicate = filters
+        .iter()
+        .map(|(column, text)| {
+            col(column.as_str())
+                .str()
+                .to_lowercase()
+                .str()
+                .contains_literal(lit(text.to_lowercase()))
+        })
+        .reduce(Expr::and);
+
+    match predicate {
+        Some(predicate) => Ok(df.lazy().filter(predicate).collect()?),
+        None => Ok(df),
+    }
+}
+
+/// Keeps only the named columns of a result, in the order given. Fails on an
+/// unknown name, listing the columns the result does have.
+fn select_columns(df: &DataFrame, columns: &[String]) -> Result<DataFrame> {
+    check_columns_exist(df, columns, "--select")?;
+    Ok(df.select(columns.iter().map(String::as_str))?)
+}
+
+/// Fails with the result's column names if any of `columns` isn't one of them
+fn check_columns_exist<'a>(
+    df: &DataFrame,
+    columns: impl IntoIterator<Item = &'a String>,
+    option: &str,
+) -> Result<()> {
+    let available = df.get_column_names_str();
+    match columns
+        .into_iter()
+        .find(|c| !available.contains(&c.as_str()))
+    {
+        Some(unknown) => Err(AthenaCliError::Validation(format!(
+            "Unknown column '{}' in {}. Available columns: {}",
+            unknown,
+            option,
+            available.join(", ")
+        ))
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Prints the results of a succeeded query page by page.
+///
+/// Rows go to stdout in the requested `format`, so they can be piped while later
+/// pages are still being fetched. Returns the number of rows printed.
+async fn follow_query_results(
+    client: &Client,
+    execution: &QueryExecution,
+    format: cli::FollowFormat,
+    verbose: bool,
+    max_rows: Option<usize>,
+) -> Result<usize> {
+    let query_execution_id = execution.query_execution_id().unwrap_or_default();
+
+    let mut header_printed = false;
+    let fetched = for_each_result_page(
+        client,
+        query_execution_id,
+        verbose,
+        max_rows,
+        |columns, rows| {
+            let mut stdout = io::stdout().lock();
+
+            if format == cli::FollowFormat::Table && !header_printed {
+                writeln!(stdout, "{}", columns.join("\t").bold())?;
+                header_printed = true;
+            }
+
+            for row in rows {
+                let values: Vec<Option<&str>> =
+                    row.data().iter().map(|d| d.var_char_value()).collect();
+                let line = match format {
+                    cli::FollowFormat::Table => values
+                        .iter()
+                        .map(|v| v.unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join("\t"),
+                    cli::FollowFormat::Ndjson => ndjson_row(columns, &values),
+                };
+                writeln!(stdout, "{}", line)?;
+            }
+            stdout.flush()?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    eprintln!("Rows returned: {}", fetched.count);
+    if fetched.truncated {
+        eprintln!(
+            "{}",
+            omitted_rows_notice(client, query_execution_id, fetched.count)
+                .await
+                .yellow()
+        );
+    }
+
+    Ok(fetched.count)
+}
+
+/// Polls the query execution until it succeeds, returning the final execution.
+/// Returns an error if the query fails or is cancelled.
+pub async fn wait_for_query(client: &Client, query_execution_id: &str) -> Result<QueryExecution> {
+    let execution = wait_until_finished(client, query_execution_id, None).await?;
+    ensure_succeeded(execution)
+}
+
+/// Returns a finished execution if it succeeded, or an error with Athena's
+/// reason if it failed or was cancelled
+fn ensure_succeeded(execution: QueryExecution) -> Result<QueryExecution> {
+    match execution.status().and_then(|s| s.state()) {
+        Some(QueryExecutionState::Succeeded) => Ok(execution),
+        _ => {
+            let error_message = if let Some(status) = execution.status() {
+                if let Some(reason) = status.state_change_reason() {
+                    format!("Query failed: {}", reason)
+                } else {
+                    "Query failed or was cancelled without specific reason".to_string()
+                }
+            } else {
+                "Query failed or was cancelled".to_string()
+            };
+            Err(AthenaCliError::QueryFailed(error_message.red().bold().to_string()).into())
+        }
+    }
+}
+
+/// Polls the query execution once a second until it succeeds, fails or is
+/// cancelled, and returns the finished execution whatever its outcome.
+/// Gives up with an error once `timeout` has elapsed, if one is given.
+pub async fn wait_until_finished(
+    client: &Client,
+    query_execution_id: &str,
+    timeout: Option<Duration>,
+) -> Result<QueryExecution> {
+    let started = Instant::now();
+
     loop {
         let status = client
             .get_query_execution()
             .query_execution_id(query_execution_id)
             .send()
-            .await?;
+            .await
+            .map_err(AthenaCliError::from_sdk)?;
 
-        if let Some(execution) = status.query_execution() {
-            match execution.status().unwrap().state().as_ref() {
-                Some(QueryExecutionState::Succeeded) => {
-                    // Print query info once before breaking
-                    if let Some(result_config) = execution.result_configuration() {
-                        if let Some(output_location) = result_config.output_location() {
-                            println!("Results S3 path: {}", output_location);
-                        }
-                    }
-
-                    if let Some(statistics) = execution.statistics() {
-                        let data_scanned = statistics.data_scanned_in_bytes().unwrap_or(0);
-                        let is_cached = data_scanned == 0;
-                        println!(
-                            "Query cache status: {}",
-                            if is_cached {
-                                String::from("Results retrieved from cache")
-                            } else {
-                                let formatted_size = Byte::from_i64(data_scanned)
-                                    .map(|b| {
-                                        b.get_appropriate_unit(byte_unit::UnitType::Decimal)
-                                            .to_string()
-                                    })
-                                    .unwrap_or_else(|| "-".to_string());
-                                format!("Fresh query execution (scanned {})", formatted_size)
-                            }
-                        );
-                    }
-                    break;
-                }
-                Some(QueryExecutionState::Failed) | Some(QueryExecutionState::Cancelled) => {
-                    let error_message = if let Some(status) = execution.status() {
-                        if let Some(reason) = status.state_change_reason() {
-                            format!("Query failed: {}", reason)
-                        } else {
-                            "Query failed or was cancelled without specific reason".to_string()
-                        }
-                    } else {
-                        "Query failed or was cancelled".to_string()
-                    };
-                    return Err(anyhow::anyhow!("{}", error_message.red().bold()));
-                }
-                _ => {
-                    thread::sleep(Duration::from_secs(1));
-                    continue;
-                }
+        let execution = status.query_execution().ok_or_else(|| {
+            AthenaCliError::NotFound(format!(
+                "No query execution found with ID: {}",
+                query_execution_id
+            ))
+        })?;
+        let state = execution.status().and_then(|s| s.state());
+
+        if matches!(
+            state,
+            Some(
+                QueryExecutionState::Succeeded
+                    | QueryExecutionState::Failed
+                    | QueryExecutionState::Cancelled
+            )
+        ) {
+            return Ok(execution.clone());
+        }
+
+        if let Some(timeout) = timeout {
+            if started.elapsed() >= timeout {
+                return Err(anyhow::anyhow!(
+                    "Timed out after {} waiting for query {} (state: {})",
+                    humantime::format_duration(timeout),
+                    query_execution_id,
+                    state.map_or("UNKNOWN", |s| s.as_str())
+                ));
             }
         }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
     }
+}
 
-    let mut all_columns: Vec<Vec<String>> = Vec::new();
+/// Describes where the results of a finished query are stored, whether they
+/// came from Athena's result cache and what the scan is estimated to cost. With
+/// `explain_reuse`, also reports Athena's own result reuse flag and the
+/// execution whose results were reused.
+fn execution_summary(
+    execution: &QueryExecution,
+    explain_reuse: bool,
+    price_per_tb: f64,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(output_location) = execution
+        .result_configuration()
+        .and_then(|c| c.output_location())
+    {
+        lines.push(format!("Results S3 path: {}", output_location));
+    }
+
+    if let Some(statistics) = execution.statistics() {
+        let data_scanned = statistics.data_scanned_in_bytes().unwrap_or(0);
+        lines.push(format!(
+            "Query cache status: {}",
+            if used_result_cache(execution) {
+                String::from("Results retrieved from cache")
+            } else {
+                format!(
+                    "Fresh query execution (scanned {})",
+                    data_scanned.format_bytes()
+                )
+            }
+        ));
+    }
+
+    if let Some(cost) = execution_cost(execution, price_per_tb) {
+        lines.push(format!("Estimated cost: {}", format_cost(cost)));
+    }
+
+    if explain_reuse {
+        lines.push(format!(
+            "Result reuse: {}",
+            ResultReuse::from_execution(execution)
+                .map_or_else(|| "not reported by Athena".to_string(), |r| r.to_string())
+        ));
+    }
+
+    lines
+}
+
+/// Reports on a succeeded execution on stderr: the summary from
+/// [`execution_summary`], the age of reused results, the data scanned against
+/// the workgroup's limit and, with `--stats`, its statistics (with `rows`
+/// returned, when the results were fetched)
+async fn report_finished_execution(
+    ctx: &Context,
+    client: &Client,
+    execution: &QueryExecution,
+    args: &cli::QueryArgs,
+    rows: Option<usize>,
+) {
+    if !ctx.quiet() {
+        for line in execution_summary(execution, args.explain_reuse, ctx.price_per_tb()) {
+            eprintln!("{}", line);
+        }
+    }
+    report_reused_age(ctx, client, execution).await;
+    report_scan_cutoff(ctx, execution, args.show_cutoff).await;
+    if args.stats {
+        print_statistics(ctx, execution, rows);
+    }
+}
+
+/// Prints how old the reused results of `execution` are, warning on stderr
+/// even in quiet mode when they are older than the configured `stale_warn_age`
+async fn report_reused_age(ctx: &Context, client: &Client, execution: &QueryExecution) {
+    let Some(age) = reused_result_age(client, execution).await else {
+        return;
+    };
+    let stale_after = ctx.stale_warn_age();
+    let notice = reused_age_notice(age, stale_after);
+    if is_stale(age, stale_after) {
+        eprintln!("{}", notice.yellow());
+    } else if !ctx.quiet() {
+        eprintln!("{}", notice);
+    }
+}
+
+/// How long ago the execution whose results were reused finished. `None` when
+/// the query ran fresh or the reused execution can't be identified or fetched.
+async fn reused_result_age(client: &Client, execution: &QueryExecution) -> Option<Duration> {
+    let Some(ResultReuse::Reused(Some(reused_id))) = ResultReuse::from_execution(execution) else {
+        return None;
+    };
+    let reused = client
+        .get_query_execution()
+        .query_execution_id(reused_id)
+        .send()
+        .await
+        .ok()?;
+    let completed = reused
+        .query_execution()?
+        .status()?
+        .completion_date_time()?
+        .secs();
+
+    let age = Utc::now().timestamp().saturating_sub(completed).max(0);
+    Some(Duration::from_secs(age as u64))
+}
+
+/// Share of the workgroup's per-query scan limit past which a query is warned
+/// about, since a slightly larger scan would be cancelled
+const CUTOFF_WARNING_SHARE: f64 = 0.8;
+
+/// Compares the data `execution` scanned with the workgroup's per-query scan
+/// limit, warning on stderr when it comes close and otherwise printing the
+/// comparison only when `always` is set
+async fn report_scan_cutoff(ctx: &Context, execution: &QueryExecution, always: bool) {
+    let Some(scanned) = execution
+        .statistics()
+        .and_then(|s| s.data_scanned_in_bytes())
+    else {
+        return;
+    };
+    let Some(cutoff) = ctx
+        .workgroup_details()
+        .await
+        .and_then(|w| w.configuration())
+        .and_then(|c| c.bytes_scanned_cutoff_per_query())
+        .filter(|&cutoff| cutoff > 0)
+    else {
+        if always && !ctx.quiet() {
+            eprintln!("Workgroup {} has no per-query scan limit", ctx.workgroup());
+        }
+        return;
+    };
+
+    let notice = scan_cutoff_notice(scanned, cutoff);
+    if near_cutoff(scanned, cutoff) {
+        eprintln!("{}", notice.yellow());
+    } else if always && !ctx.quiet() {
+        eprintln!("{}", notice);
+    }
+}
+
+/// Whether a scan is close enough to the cutoff to warn about
+fn near_cutoff(scanned: i64, cutoff: i64) -> bool {
+    scanned as f64 >= cutoff as f64 * CUTOFF_WARNING_SHARE
+}
+
+/// Describes the data scanned against the cutoff, as a warning when it's close
+fn scan_cutoff_notice(scanned: i64, cutoff: i64) -> String {
+    let comparison = format!(
+        "{} of the workgroup's {} per-query limit ({:.0}%)",
+        scanned.format_bytes(),
+        cutoff.format_bytes(),
+        scanned as f64 / cutoff as f64 * 100.0
+    );
+    if near_cutoff(scanned, cutoff) {
+        format!(
+            "Warning: scanned {}; queries scanning more than the limit are cancelled",
+            comparison
+        )
+    } else {
+        format!("Data scanned: {}", comparison)
+    }
+}
+
+/// Whether reused results of the given age are older than `stale_after`
+fn is_stale(age: Duration, stale_after: Option<Duration>) -> bool {
+    stale_after.is_some_and(|stale_after| age > stale_after)
+}
+
+/// Describes the age of reused results, as a warning once they're stale
+fn reused_age_notice(age: Duration, stale_after: Option<Duration>) -> String {
+    let age_text = humantime::format_duration(Duration::from_secs(age.as_secs()));
+    match stale_after {
+        Some(stale_after) if is_stale(age, Some(stale_after)) => format!(
+            "Warning: reused results are {} old, older than stale_warn_age ({}); use --no-reuse for fresh results",
+            age_text,
+            humantime::format_duration(stale_after)
+        ),
+        _ => format!("Reused results are {} old", age_text),
+    }
+}
+
+/// Statistics shown by `query --stats`, in the order they are listed
+const STATISTICS_FIELDS: [InspectField; 6] = [
+    InspectField::EngineExecutionTime,
+    InspectField::TotalExecutionTime,
+    InspectField::QueryPlanningTime,
+    InspectField::QueryQueueTime,
+    InspectField::ServiceProcessingTime,
+    InspectField::DataScanned,
+];
+
+/// Prints the statistics of a finished query to stderr in the same table as
+/// `inspect`, with the number of rows returned when the results were fetched
+fn print_statistics(ctx: &Context, execution: &QueryExecution, rows: Option<usize>) {
+    let timestamps = ctx.timestamp_format();
+    let mut table = prettytable::Table::new();
+    table.add_row(prettytable::row![Fb->"Field", Fb->"Value"]);
+    for field in STATISTICS_FIELDS {
+        let value = get_field_value(execution, field, &timestamps, ctx.price_per_tb());
+        table.add_row(prettytable::row![Fb->field.to_string(), value]);
+    }
+    if let Some(rows) = rows {
+        table.add_row(prettytable::row![Fb->"Rows Returned", rows]);
+    }
+
+    eprintln!("\n{}", "Query Statistics".bold());
+    eprint!("{}", table_style::styled(&table));
+}
+
+/// Rows handed to the page callback by [`for_each_result_page`]
+struct FetchedRows {
+    count: usize,
+    /// Whether the row cap stopped pagination before the end of the results
+    truncated: bool,
+}
+
+/// Pages through the results of a succeeded query (100 rows per page), calling
+/// `on_page` with the column names and the data rows of each page. With
+/// `max_rows`, stops once that many rows have been passed on.
+async fn for_each_result_page<F>(
+    client: &Client,
+    query_execution_id: &str,
+    verbose: bool,
+    max_rows: Option<usize>,
+    mut on_page: F,
+) -> Result<FetchedRows>
+where
+    F: FnMut(&[String], &[Row]) -> Result<()>,
+{
     let mut column_names: Vec<String> = Vec::new();
     let mut next_token: Option<String> = None;
+    let mut total_rows = 0;
+    let mut truncated = false;
 
     // Get first page and column names
     let mut results = client
@@ -282,7 +1586,6 @@ async fn get_query_results(client: &Client, query_execution_id: &str) -> Result<
                 .iter()
                 .map(|d| d.var_char_value().unwrap_or_default().to_string())
                 .collect();
-            all_columns = vec![Vec::new(); column_names.len()];
         }
     }
 
@@ -290,26 +1593,40 @@ async fn get_query_results(client: &Client, query_execution_id: &str) -> Result<
     let mut page_count = 1;
     loop {
         if let Some(rs) = results.result_set() {
+            // The first page starts with the header row
             let start_idx = if next_token.is_none() { 1 } else { 0 };
-            let rows_count = rs.rows().len() - start_idx;
+            let mut rows = rs.rows().get(start_idx..).unwrap_or_default();
 
-            println!("Processing page {}: {} rows", page_count, rows_count);
-
-            for row in rs.rows().iter().skip(start_idx) {
-                for (i, data) in row.data().iter().enumerate() {
-                    all_columns[i].push(data.var_char_value().unwrap_or_default().to_string());
+            if let Some(max_rows) = max_rows {
+                let remaining = max_rows.saturating_sub(total_rows);
+                if rows.len() > remaining {
+                    rows = &rows[..remaining];
+                    truncated = true;
                 }
             }
+
+            if verbose {
+                eprintln!("Processing page {}: {} rows", page_count, rows.len());
+            }
+
+            on_page(&column_names, rows)?;
+            total_rows += rows.len();
         }
 
         next_token = results.next_token().map(|s| s.to_string());
 
-        if next_token.is_none() {
-            println!(
-                "Finished processing {} pages, total rows: {}",
-                page_count,
-                all_columns[0].len()
-            );
+        // Rows beyond the cap are never fetched
+        if next_token.is_some() && max_rows.is_some_and(|max_rows| total_rows >= max_rows) {
+            truncated = true;
+        }
+
+        if next_token.is_none() || truncated {
+            if verbose {
+                eprintln!(
+                    "Finished processing {} pages, total rows: {}",
+                    page_count, total_rows
+                );
+            }
             break;
         }
 
@@ -323,14 +1640,384 @@ async fn get_query_results(client: &Client, query_execution_id: &str) -> Result<
             .await?;
     }
 
-    // Create DataFrame
-    let series = all_columns
+    Ok(FetchedRows {
+        count: total_rows,
+        truncated,
+    })
+}
+
+/// Number of rows a finished query output, from Athena's runtime statistics
+async fn output_rows(client: &Client, query_execution_id: &str) -> Option<i64> {
+    client
+        .get_query_runtime_statistics()
+        .query_execution_id(query_execution_id)
+        .send()
+        .await
+        .ok()
+        .and_then(|stats| {
+            stats
+                .query_runtime_statistics()
+                .and_then(|s| s.rows())
+                .and_then(|rows| rows.output_rows())
+        })
+}
+
+/// Reads the count returned by a `--count-only` query, the single value after
+/// the header row
+async fn counted_rows(client: &Client, query_execution_id: &str) -> Result<u64> {
+    let results = client
+        .get_query_results()
+        .query_execution_id(query_execution_id)
+        .max_results(2)
+        .send()
+        .await
+        .map_err(AthenaCliError::from_sdk)?;
+
+    results
+        .result_set()
+        .and_then(|set| set.rows().get(1))
+        .and_then(|row| row.data().first())
+        .and_then(|datum| datum.var_char_value())
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("Query {} didn't return a row count", query_execution_id))
+}
+
+/// Explains that only the first `shown` rows were fetched, including how many
+/// were left out when Athena's runtime statistics report the total
+async fn omitted_rows_notice(client: &Client, query_execution_id: &str, shown: usize) -> String {
+    let total = output_rows(client, query_execution_id)
+        .await
+        .and_then(|total| usize::try_from(total).ok());
+
+    match total {
+        Some(total) if total > shown => format!(
+            "Showing the first {} rows, {} more omitted (use --max-rows 0 for all rows)",
+            shown,
+            total - shown
+        ),
+        _ => format!(
+            "Showing the first {} rows, more were omitted (use --max-rows 0 for all rows)",
+            shown
+        ),
+    }
+}
+
+/// A statement that writes its results somewhere instead of returning rows
+#[derive(Debug, PartialEq)]
+enum WriteStatement {
+    /// CREATE TABLE AS SELECT, with the created table when it's known
+    CreateTableAs(Option<String>),
+    /// UNLOAD, with the S3 location written to when it's known
+    Unload(Option<String>),
+}
+
+impl WriteStatement {
+    /// Recognizes CTAS and UNLOAD from the statement type Athena reports, or
+    /// from the query text for executions that don't report one
+    fn detect(execution: &QueryExecution, query: &str) -> Option<Self> {
+        match execution.substatement_type() {
+            Some("CREATE_TABLE_AS_SELECT") => {
+                Some(Self::CreateTableAs(validation::created_table(query)))
+            }
+            Some("UNLOAD") => Some(Self::Unload(validation::unload_location(query))),
+            Some(_) => None,
+            None => validation::created_table(query)
+                .map(|table| Self::CreateTableAs(Some(table)))
+                .or_else(|| {
+                    validation::unload_location(query).map(|location| Self::Unload(Some(location)))
+                }),
+        }
+    }
+
+    /// Prints the created table or written location and the number of rows
+    /// written. Quiet mode prints only the table or location.
+    fn report(&self, rows: Option<i64>, quiet: bool) {
+        let (message, target) = match self {
+            Self::CreateTableAs(Some(table)) => ("Created table:", Some(table)),
+            Self::CreateTableAs(None) => ("Table created", None),
+            Self::Unload(Some(location)) => ("Results unloaded to:", Some(location)),
+            Self::Unload(None) => ("Results unloaded", None),
+        };
+
+        if quiet {
+            if let Some(target) = target {
+                println!("{}", target);
+            }
+            return;
+        }
+        match target {
+            Some(target) => println!("{} {}", message, target.bright_green()),
+            None => println!("{}", message),
+        }
+        if let Some(rows) = rows {
+            println!("Rows written: {}", rows);
+        }
+    }
+}
+
+/// Wraps `query` in an UNLOAD statement that writes its results to `location`
+/// in the given format. The query goes on its own lines so a trailing `--`
+/// comment can't swallow the closing parenthesis.
+fn unload_statement(query: &str, location: &str, format: cli::UnloadFormat) -> String {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    let properties = match format {
+        cli::UnloadFormat::Parquet => "format = 'PARQUET'",
+        cli::UnloadFormat::Orc => "format = 'ORC'",
+        cli::UnloadFormat::Avro => "format = 'AVRO'",
+        cli::UnloadFormat::Json => "format = 'JSON'",
+        cli::UnloadFormat::Csv => "format = 'TEXTFILE', field_delimiter = ','",
+    };
+
+    format!(
+        "UNLOAD (\n{}\n) TO '{}' WITH ({})",
+        query,
+        location.replace('\'', "''"),
+        properties
+    )
+}
+
+/// Renders a row as a single-line JSON object, keeping the column order and
+/// mapping missing values to `null`
+fn ndjson_row(columns: &[String], values: &[Option<&str>]) -> String {
+    let fields: Vec<String> = columns
         .iter()
-        .zip(column_names.iter())
-        .map(|(col, name)| Series::new(name.into(), col))
-        .map(|s| s.into_column())
+        .zip(values)
+        .map(|(column, value)| {
+            format!(
+                "{}:{}",
+                serde_json::Value::from(column.as_str()),
+                value.map_or(serde_json::Value::Null, serde_json::Value::from)
+            )
+        })
         .collect();
 
-    // Convert Series to Columns and create DataFrame
-    Ok(DataFrame::new(series)?)
+    format!("{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_cutoff_notice() {
+        let cutoff = 10_000_000_000;
+        assert!(!near_cutoff(2_000_000_000, cutoff));
+        assert_eq!(
+            scan_cutoff_notice(2_000_000_000, cutoff),
+            "Data scanned: 2 GB of the workgroup's 10 GB per-query limit (20%)"
+        );
+
+        assert!(near_cutoff(8_500_000_000, cutoff));
+        assert_eq!(
+            scan_cutoff_notice(9_500_000_000, cutoff),
+            "Warning: scanned 9.5 GB of the workgroup's 10 GB per-query limit (95%); \
+             queries scanning more than the limit are cancelled"
+        );
+    }
+
+    #[test]
+    fn test_reused_age_notice() {
+        let age = Duration::from_secs(2 * 3600 + 5);
+        assert_eq!(reused_age_notice(age, None), "Reused results are 2h 5s old");
+        assert_eq!(
+            reused_age_notice(age, Some(Duration::from_secs(3 * 3600))),
+            "Reused results are 2h 5s old"
+        );
+
+        let stale_after = Duration::from_secs(3600);
+        assert!(is_stale(age, Some(stale_after)));
+        assert!(!is_stale(age, None));
+        assert_eq!(
+            reused_age_notice(age, Some(stale_after)),
+            "Warning: reused results are 2h 5s old, older than stale_warn_age (1h); \
+             use --no-reuse for fresh results"
+        );
+    }
+
+    #[test]
+    fn test_reuse_max_age_minutes() {
+        // Zero disables reuse entirely
+        assert_eq!(reuse_max_age_minutes(Duration::from_secs(0)), None);
+
+        // Sub-minute durations round down to a zero-minute window
+        assert_eq!(reuse_max_age_minutes(Duration::from_secs(30)), Some(0));
+
+        // Partial minutes are truncated
+        assert_eq!(reuse_max_age_minutes(Duration::from_secs(90)), Some(1));
+    }
+
+    #[test]
+    fn test_unload_statement() {
+        assert_eq!(
+            unload_statement(
+                "SELECT * FROM orders -- all of them\n;",
+                "s3://exports/orders/",
+                cli::UnloadFormat::Parquet
+            ),
+            "UNLOAD (\nSELECT * FROM orders -- all of them\n) TO 's3://exports/orders/' WITH (format = 'PARQUET')"
+        );
+        assert_eq!(
+            unload_statement("SELECT 1", "s3://exports/it's/", cli::UnloadFormat::Csv),
+            "UNLOAD (\nSELECT 1\n) TO 's3://exports/it''s/' WITH (format = 'TEXTFILE', field_delimiter = ',')"
+        );
+    }
+
+    #[test]
+    fn test_min_median_max() {
+        assert_eq!(min_median_max(&[]), None);
+        assert_eq!(min_median_max(&[7]), Some((7, 7, 7)));
+        assert_eq!(min_median_max(&[30, 10, 20]), Some((10, 20, 30)));
+        assert_eq!(min_median_max(&[40, 10, 20, 30]), Some((10, 25, 40)));
+    }
+
+    #[test]
+    fn test_ndjson_row() {
+        let columns = vec!["id".to_string(), "name".to_string(), "note".to_string()];
+
+        assert_eq!(
+            ndjson_row(&columns, &[Some("1"), Some("say \"hi\""), None]),
+            r#"{"id":"1","name":"say \"hi\"","note":null}"#
+        );
+    }
+
+    #[test]
+    fn test_select_columns() {
+        let df = df!(
+            "id" => ["1", "2"],
+            "name" => ["a", "b"],
+            "note" => ["x", "y"]
+        )
+        .unwrap();
+
+        let selected = select_columns(&df, &["note".to_string(), "id".to_string()]).unwrap();
+        assert_eq!(selected.get_column_names_str(), vec!["note", "id"]);
+        assert_eq!(selected.height(), 2);
+
+        let err = select_columns(&df, &["nmae".to_string()]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Available columns: id, name, note"));
+    }
+
+    #[test]
+    fn test_detect_write_statement() {
+        let reported = |substatement_type: &str| {
+            QueryExecution::builder()
+                .substatement_type(substatement_type)
+                .build()
+        };
+        let unreported = QueryExecution::builder().build();
+        let ctas = "CREATE TABLE reports.daily AS SELECT * FROM orders";
+        let unload = "UNLOAD (SELECT * FROM orders) TO 's3://bucket/out/' WITH (format = 'JSON')";
+
+        assert_eq!(
+            WriteStatement::detect(&reported("CREATE_TABLE_AS_SELECT"), ctas),
+            Some(WriteStatement::CreateTableAs(Some(
+                "reports.daily".to_string()
+            )))
+        );
+        assert_eq!(
+            WriteStatement::detect(&unreported, unload),
+            Some(WriteStatement::Unload(Some("s3://bucket/out/".to_string())))
+        );
+        assert_eq!(
+            WriteStatement::detect(&reported("SELECT"), "SELECT * FROM orders"),
+            None
+        );
+        assert_eq!(
+            WriteStatement::detect(&unreported, "SELECT * FROM orders"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_configured_json_format_prints_rows() {
+        // app.default_format = "json" with nothing saved prints the rows as JSON
+        let format = result_format(OutputFormat::Json, None, None);
+        assert_eq!(format, Some(cli::ResultFormat::Json));
+        assert!(!prints_json_summary(format, None));
+
+        // Saved results get the summary instead, unless the file names its format
+        let format = result_format(OutputFormat::Json, None, Some("results/"));
+        assert!(prints_json_summary(format, Some("results/")));
+        let format = result_format(OutputFormat::Json, None, Some("results.csv"));
+        assert!(!prints_json_summary(format, Some("results.csv")));
+
+        assert_eq!(result_format(OutputFormat::Table, None, None), None);
+    }
+
+    #[test]
+    fn test_render_results_vertical() {
+        let df = df!(
+            "id" => ["1", "2"],
+            "customer_name" => ["a", "b"]
+        )
+        .unwrap();
+
+        let rendered = render_results(&df, true);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "*************************** 1. row ***************************",
+                "           id: 1",
+                "customer_name: a",
+                "*************************** 2. row ***************************",
+                "           id: 2",
+                "customer_name: b",
+            ]
+        );
+        assert_eq!(table_width(&df), 5 + 16 + 1);
+    }
+
+    #[test]
+    fn test_truncate_columns() {
+        let df = df!(
+            "id" => ["1", "2"],
+            "note" => ["short", "a much longer note"]
+        )
+        .unwrap();
+
+        let truncated = truncate_columns(&df, 10).unwrap();
+        let notes = truncated.column("note").unwrap().str().unwrap();
+        assert_eq!(notes.get(0), Some("short"));
+        assert_eq!(notes.get(1), Some("a much ..."));
+        assert_eq!(truncated.get_column_names_str(), vec!["id", "note"]);
+
+        let unlimited = truncate_columns(&df, 0).unwrap();
+        assert!(unlimited.equals(&df));
+    }
+
+    #[test]
+    fn test_filter_rows() {
+        let df = df!(
+            "id" => ["1", "2", "3"],
+            "status" => ["FAILED", "succeeded", "Failed"],
+            "region" => ["eu-west-1", "eu-west-1", "us-east-1"]
+        )
+        .unwrap();
+
+        let failed =
+            filter_rows(df.clone(), &[("status".to_string(), "fail".to_string())]).unwrap();
+        assert_eq!(failed.height(), 2);
+
+        let failed_in_eu = filter_rows(
+            df.clone(),
+            &[
+                ("status".to_string(), "fail".to_string()),
+                ("region".to_string(), "EU-".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            failed_in_eu.column("id").unwrap().str().unwrap().get(0),
+            Some("1")
+        );
+        assert_eq!(failed_in_eu.height(), 1);
+
+        let err = filter_rows(df, &[("state".to_string(), "fail".to_string())]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Available columns: id, status, region"));
+    }
 }
@@ -0,0 +1,193 @@
+//! Interactive session for running several queries without restarting the CLI.
+//!
+//! Config and AWS credentials are resolved once, then each statement (terminated
+//! by `;`) runs through the regular `query` command. Lines starting with `\` are
+//! meta-commands:
+//!
+//! - `\d <table>` describes a table
+//! - `\h` lists the queries run in this session
+//! - `\?` shows help
+//! - `\q` quits
+
+use crate::cli::{DescribeTableArgs, QueryArgs, ReplArgs};
+use crate::commands::{database, query};
+use crate::context::Context;
+use anyhow::Result;
+use colored::Colorize;
+use std::io::{self, BufRead, Write};
+
+const PROMPT: &str = "athena> ";
+const CONTINUATION_PROMPT: &str = "     -> ";
+
+/// A complete input from the session
+#[derive(Debug, PartialEq)]
+enum Statement {
+    /// A `\` meta-command and its argument
+    Meta(String, Option<String>),
+    /// SQL terminated by `;`
+    Query(String),
+}
+
+/// Collects input lines until they form a complete statement
+#[derive(Debug, Default)]
+struct InputBuffer {
+    lines: Vec<String>,
+}
+
+impl InputBuffer {
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Adds a line, returning a statement once one is complete
+    fn push_line(&mut self, line: &str) -> Option<Statement> {
+        let line = line.trim_end();
+
+        // Meta-commands are only recognized at the start of a statement
+        if self.is_empty() {
+            if let Some(command) = line.trim_start().strip_prefix('\\') {
+                let mut parts = command.splitn(2, char::is_whitespace);
+                let name = parts.next().unwrap_or_default().to_string();
+                let arg = parts
+                    .next()
+                    .map(str::trim)
+                    .filter(|arg| !arg.is_empty())
+                    .map(str::to_string);
+                return Some(Statement::Meta(name, arg));
+            }
+            if line.trim().is_empty() {
+                return None;
+            }
+        }
+
+        self.lines.push(line.to_string());
+        if !line.ends_with(';') {
+            return None;
+        }
+
+        let sql = self.lines.join("\n");
+        self.lines.clear();
+        Some(Statement::Query(
+            sql.trim().trim_end_matches(';').trim_end().to_string(),
+        ))
+    }
+}
+
+pub async fn run(ctx: &Context, args: &ReplArgs) -> Result<()> {
+    println!(
+        "{} Type SQL ending with ';', or \\? for help.",
+        "Athena interactive session.".bold()
+    );
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut buffer = InputBuffer::default();
+    let mut history: Vec<String> = Vec::new();
+
+    loop {
+        print!(
+            "{}",
+            if buffer.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            }
+        );
+        io::stdout().flush()?;
+
+        // End of input (Ctrl-D) ends the session
+        let Some(line) = lines.next().transpose()? else {
+            println!();
+            break;
+        };
+
+        match buffer.push_line(&line) {
+            None => {}
+            Some(Statement::Query(sql)) => {
+                history.push(sql.clone());
+                let query_args = QueryArgs::for_sql(sql, args.reuse_time);
+                report(query::execute(ctx, &query_args).await);
+            }
+            Some(Statement::Meta(name, arg)) => match (name.as_str(), arg) {
+                ("q", _) => break,
+                ("d", Some(table)) => {
+                    let describe_args = DescribeTableArgs {
+                        table,
+                        db: None,
+                        partitions: false,
+                        columns_types: false,
+                        columns_order: Default::default(),
+                        format: Default::default(),
+                    };
+                    report(database::describe_table(ctx, &describe_args).await);
+                }
+                ("d", None) => println!("Usage: \\d <table>"),
+                ("h", _) => {
+                    for (index, sql) in history.iter().enumerate() {
+                        println!("{:>3}  {}", index + 1, sql);
+                    }
+                }
+                ("?", _) => print_help(),
+                _ => println!("Unknown command: \\{} (\\? for help)", name),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a failed statement's error without ending the session
+fn report(result: Result<()>) {
+    if let Err(e) = result {
+        eprintln!("❌ Error: {}", e.to_string().bright_red());
+    }
+}
+
+fn print_help() {
+    println!("  <sql>;        Run a query (may span several lines)");
+    println!("  \\d <table>    Describe a table ('database.table' or 'table')");
+    println!("  \\h            List queries run in this session");
+    println!("  \\?            Show this help");
+    println!("  \\q            Quit (or press Ctrl-D)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_buffer_collects_statements() {
+        let mut buffer = InputBuffer::default();
+
+        // Blank lines between statements are ignored
+        assert_eq!(buffer.push_line("   "), None);
+        assert!(buffer.is_empty());
+
+        // A query is complete once a line ends with ';'
+        assert_eq!(buffer.push_line("SELECT *"), None);
+        assert_eq!(
+            buffer.push_line("FROM orders;"),
+            Some(Statement::Query("SELECT *\nFROM orders".to_string()))
+        );
+        assert!(buffer.is_empty());
+
+        assert_eq!(
+            buffer.push_line("\\d sales.orders"),
+            Some(Statement::Meta(
+                "d".to_string(),
+                Some("sales.orders".to_string())
+            ))
+        );
+        assert_eq!(
+            buffer.push_line("\\q"),
+            Some(Statement::Meta("q".to_string(), None))
+        );
+
+        // Backslashes inside a query are just SQL
+        assert_eq!(buffer.push_line("SELECT 'a'"), None);
+        assert_eq!(
+            buffer.push_line("\\q;"),
+            Some(Statement::Query("SELECT 'a'\n\\q".to_string()))
+        );
+    }
+}
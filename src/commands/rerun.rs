@@ -0,0 +1,42 @@
+//! Runs the SQL of a previous query execution again through `query`.
+
+use crate::cli::{QueryArgs, RerunArgs};
+use crate::commands::{history, query};
+use crate::context::Context;
+use crate::error::AthenaCliError;
+use anyhow::Result;
+use colored::Colorize;
+
+/// Fetches the original SQL of `query_id` and runs it in the current
+/// workgroup, against the current database unless `--same-context` is given
+pub async fn rerun(ctx: &Context, args: &RerunArgs) -> Result<()> {
+    let query_id = history::pick::query_id(ctx, args.query_id.as_deref()).await?;
+    let result = ctx
+        .create_athena_client()
+        .get_query_execution()
+        .query_execution_id(&query_id)
+        .send()
+        .await?;
+    let execution = result.query_execution().ok_or_else(|| {
+        AthenaCliError::NotFound(format!("No query execution found with ID: {}", query_id))
+    })?;
+    let sql = execution
+        .query()
+        .filter(|sql| !sql.trim().is_empty())
+        .ok_or_else(|| AthenaCliError::NotFound(format!("Query {} has no SQL to run", query_id)))?;
+
+    if !ctx.quiet() {
+        eprintln!("Rerunning query {}", query_id.bright_green());
+    }
+    let query_args = QueryArgs::for_sql(sql.to_string(), args.reuse_time);
+
+    if args.same_context {
+        let context = execution.query_execution_context();
+        let ctx = ctx.with_query_context(
+            context.and_then(|c| c.database()).map(str::to_string),
+            context.and_then(|c| c.catalog()).map(str::to_string),
+        );
+        return query::execute(&ctx, &query_args).await;
+    }
+    query::execute(ctx, &query_args).await
+}
@@ -0,0 +1,79 @@
+//! Shows which AWS identity and Athena settings the CLI resolved, for diagnosing
+//! "wrong account" or "wrong workgroup" problems.
+
+use crate::commands::workgroup::workgroup_engine_version;
+use crate::context::Context;
+use anyhow::{Context as _, Result};
+use colored::Colorize;
+
+/// Prints the effective settings, then the caller identity reported by STS.
+/// Settings are printed first so they're visible even when the credentials fail.
+pub async fn whoami(ctx: &Context) -> Result<()> {
+    let settings = [
+        (
+            "Profile",
+            ctx.profile()
+                .unwrap_or_else(|| "(default credential chain)".to_string()),
+        ),
+        ("Region", ctx.region()),
+        (
+            "Endpoint URL",
+            ctx.endpoint_url().unwrap_or_else(|| "-".to_string()),
+        ),
+        ("Workgroup", ctx.workgroup()),
+        ("Catalog", ctx.catalog()),
+        (
+            "Database",
+            ctx.database().unwrap_or_else(|| "-".to_string()),
+        ),
+        (
+            "Output Location",
+            ctx.output_location().unwrap_or_else(|| "-".to_string()),
+        ),
+    ];
+    for (label, value) in settings {
+        println!("{}: {}", label.bold(), value);
+    }
+
+    let identity = ctx
+        .create_sts_client()
+        .get_caller_identity()
+        .send()
+        .await
+        .context("Failed to resolve AWS caller identity")?;
+
+    println!();
+    println!(
+        "{}: {}",
+        "Account".bold(),
+        identity.account().unwrap_or("-").bright_green()
+    );
+    println!("{}: {}", "ARN".bold(), identity.arn().unwrap_or("-"));
+    println!(
+        "{}: {}",
+        "User ID".bold(),
+        identity.user_id().unwrap_or("-")
+    );
+
+    // Engine versions differ in SQL dialect, which explains some query failures
+    let workgroup = ctx
+        .create_athena_client()
+        .get_work_group()
+        .work_group(ctx.workgroup())
+        .send()
+        .await
+        .ok();
+    let engine_version = workgroup
+        .as_ref()
+        .and_then(|r| r.work_group())
+        .and_then(|w| w.configuration())
+        .and_then(|c| c.engine_version())
+        .and_then(workgroup_engine_version);
+    println!(
+        "{}: {}",
+        "Engine Version".bold(),
+        engine_version.unwrap_or("-")
+    );
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
-use crate::cli::WorkgroupArgs;
+use crate::cli::{WorkgroupArgs, WorkgroupSortBy};
 use crate::context::Context;
+use crate::utils::filter;
 use anyhow::Result;
+use aws_sdk_athena::types::{EngineVersion, WorkGroupState, WorkGroupSummary};
 
 pub async fn list(ctx: &Context, args: &WorkgroupArgs) -> Result<()> {
     let client = ctx.create_athena_client();
@@ -14,11 +16,109 @@ pub async fn list(ctx: &Context, args: &WorkgroupArgs) -> Result<()> {
         .await?;
 
     // work_groups() returns a slice reference, not an Option
-    for workgroup in result.work_groups() {
+    let mut workgroups = filter::filter_items(
+        result.work_groups(),
+        args.filter.as_deref(),
+        args.case_sensitive,
+        |workgroup| workgroup.name().unwrap_or_default(),
+    );
+    if let Some(state) = args.state {
+        workgroups
+            .retain(|workgroup| workgroup.state().map(|s| s.as_str()) == Some(state.as_str()));
+    }
+    if let Some(sort_by) = args.sort_by {
+        sort_workgroups(&mut workgroups, sort_by);
+    }
+
+    if workgroups.is_empty() {
+        match (&args.filter, args.state) {
+            (Some(pattern), _) => println!("No workgroups found matching filter: {}", pattern),
+            (None, Some(state)) => println!("No {} workgroups found", state.as_str()),
+            (None, None) => {}
+        }
+        return Ok(());
+    }
+
+    for workgroup in workgroups {
         if let Some(name) = workgroup.name() {
-            println!("- {}", name);
+            // Disabled workgroups can't run queries, so they stand out
+            let disabled = if workgroup.state() == Some(&WorkGroupState::Disabled) {
+                " [DISABLED]"
+            } else {
+                ""
+            };
+            match workgroup
+                .engine_version()
+                .and_then(workgroup_engine_version)
+            {
+                Some(version) => println!("- {} ({}){}", name, version, disabled),
+                None => println!("- {}{}", name, disabled),
+            }
         }
     }
 
     Ok(())
 }
+
+/// Sorts workgroups by name, or by creation time with the name breaking ties.
+/// Workgroups without a creation time come last.
+fn sort_workgroups(workgroups: &mut [&WorkGroupSummary], sort_by: WorkgroupSortBy) {
+    match sort_by {
+        WorkgroupSortBy::Name => workgroups.sort_by_key(|workgroup| workgroup.name()),
+        WorkgroupSortBy::Created => workgroups.sort_by_key(|workgroup| {
+            (
+                workgroup.creation_time().is_none(),
+                workgroup.creation_time().map(|time| time.secs()),
+                workgroup.name(),
+            )
+        }),
+    }
+}
+
+/// Engine version queries in a workgroup run on. Workgroups set to upgrade
+/// automatically report "AUTO" as selected, so the effective version is preferred.
+pub fn workgroup_engine_version(version: &EngineVersion) -> Option<&str> {
+    version
+        .effective_engine_version()
+        .or(version.selected_engine_version())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_athena::primitives::DateTime;
+
+    fn workgroup(name: &str, created: Option<i64>) -> WorkGroupSummary {
+        WorkGroupSummary::builder()
+            .name(name)
+            .set_creation_time(created.map(DateTime::from_secs))
+            .build()
+    }
+
+    #[test]
+    fn test_sort_workgroups() {
+        let summaries = [
+            workgroup("primary", Some(300)),
+            workgroup("analytics", None),
+            workgroup("etl", Some(100)),
+            workgroup("adhoc", Some(300)),
+        ];
+        let names = |sort_by| {
+            let mut workgroups: Vec<&WorkGroupSummary> = summaries.iter().collect();
+            sort_workgroups(&mut workgroups, sort_by);
+            workgroups
+                .iter()
+                .map(|w| w.name().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(WorkgroupSortBy::Name),
+            ["adhoc", "analytics", "etl", "primary"]
+        );
+        assert_eq!(
+            names(WorkgroupSortBy::Created),
+            ["etl", "adhoc", "primary", "analytics"]
+        );
+    }
+}
@@ -1,18 +1,25 @@
 use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::Duration};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub aws: AwsConfig,
     pub app: AppConfig,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsConfig {
     pub region: Option<String>,
     pub workgroup: Option<String>,
+    /// S3 location for query results. Empty uses the workgroup's configured location
+    #[serde(default)]
     pub output_location: String,
     pub catalog: Option<String>,
     pub database: Option<String>,
@@ -33,10 +40,58 @@ impl Default for HistorySortBy {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Timezone used when displaying timestamps
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Timezone {
+    Utc,
+    #[default]
+    Local,
+}
+
+/// Border style of the tables the CLI prints
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Borders and a line between rows
+    #[default]
+    Ascii,
+    /// Aligned columns without any lines
+    Clean,
+    /// GitHub-flavored markdown, for pasting into docs and pull requests
+    Markdown,
+    /// Column separators only
+    Borderless,
+}
+
+/// Format of a command's output when no --format is given
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Csv,
+    Json,
+    Ndjson,
+    Parquet,
+    Arrow,
+}
+
+/// Settings of a single command, in its `[app.<command>]` section
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CommandConfig {
+    /// Output format of the command, overriding `default_format`
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     #[serde(with = "humantime_serde")]
     pub query_reuse_time: Duration,
+    /// Reused results older than this are reported with a warning
+    #[serde(default, with = "humantime_serde")]
+    pub stale_warn_age: Option<Duration>,
     pub max_rows: usize,
     /// Default number of history items to show
     #[serde(default = "default_history_size")]
@@ -47,12 +102,57 @@ pub struct AppConfig {
     /// Fields to display in inspect view
     #[serde(default)]
     pub inspect_fields: Option<Vec<String>>,
+    /// strftime-style format used to display timestamps
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Timezone used to display timestamps (utc or local)
+    #[serde(default)]
+    pub timezone: Timezone,
+    /// LIMIT added to queries that don't have one, unless --no-limit is given
+    #[serde(default)]
+    pub auto_limit: Option<u64>,
+    /// Dollars per TB scanned, used to estimate query costs
+    #[serde(default = "default_price_per_tb")]
+    pub price_per_tb: f64,
+    /// Per-region overrides of price_per_tb, keyed by region name
+    #[serde(default)]
+    pub region_price_per_tb: HashMap<String, f64>,
+    /// Longer table values are cut off with "..." (0 for no limit)
+    #[serde(default = "default_max_column_width")]
+    pub max_column_width: usize,
+    /// Directory of .sql files that `query --name` runs and `library list` shows
+    #[serde(default)]
+    pub query_library_dir: Option<PathBuf>,
+    /// Border style of printed tables (ascii, clean, markdown or borderless)
+    #[serde(default)]
+    pub table_style: TableStyle,
+    /// Output format of commands without --format or their own `format` setting
+    #[serde(default)]
+    pub default_format: Option<OutputFormat>,
+    /// Settings of the query command
+    #[serde(default)]
+    pub query: CommandConfig,
+    /// Settings of the history command
+    #[serde(default)]
+    pub history: CommandConfig,
 }
 
 fn default_history_size() -> i32 {
     20
 }
 
+fn default_price_per_tb() -> f64 {
+    5.0
+}
+
+fn default_max_column_width() -> usize {
+    50
+}
+
+pub fn default_date_format() -> String {
+    "%Y-%m-%d %H:%M:%S".to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -66,50 +166,279 @@ impl Default for Config {
             },
             app: AppConfig {
                 query_reuse_time: Duration::from_secs(3600), // 1 hour
+                stale_warn_age: None,
                 max_rows: 1000,
                 history_size: 20,
                 history_fields: None,
                 inspect_fields: None,
+                date_format: default_date_format(),
+                timezone: Timezone::default(),
+                auto_limit: None,
+                price_per_tb: default_price_per_tb(),
+                region_price_per_tb: HashMap::new(),
+                max_column_width: default_max_column_width(),
+                query_library_dir: None,
+                table_style: TableStyle::default(),
+                default_format: None,
+                query: CommandConfig::default(),
+                history: CommandConfig::default(),
             },
         }
     }
 }
 
+/// Project file looked up from the current directory upwards, whose settings
+/// override the user config (e.g. to pin a repository's region and workgroup)
+pub const PROJECT_FILE_NAME: &str = ".athena-cli.toml";
+
 impl Config {
-    pub fn load() -> Result<Self> {
+    /// Load the config file (TOML, JSON or YAML, see [`get_config_path`]),
+    /// creating a default TOML one if missing, with the settings of the
+    /// nearest `.athena-cli.toml` project file merged over it.
+    /// When `verbose` is set, the resolved paths and key settings are printed.
+    pub fn load(verbose: bool) -> Result<Self> {
         let config_path = get_config_path()?;
 
-        println!("Looking for config at: {}", config_path.display());
+        if verbose {
+            eprintln!("Looking for config at: {}", config_path.display());
+        }
 
         if !config_path.exists() {
-            println!("Config file not found, creating default");
+            if verbose {
+                eprintln!("Config file not found, creating default");
+            }
             let config = Config::default();
-            std::fs::create_dir_all(config_path.parent().unwrap())?;
-            std::fs::write(&config_path, toml::to_string_pretty(&config)?)?;
-            return Ok(config);
+            config.save(&config_path)?;
+            eprintln!(
+                "Created a default config at {}. Run 'athena-cli config init' to set it up.",
+                config_path.display()
+            );
         }
 
-        println!("Loading config from: {}", config_path.display());
-        let config = config::Config::builder()
-            .add_source(config::File::from(config_path))
-            .build()?;
+        if verbose {
+            eprintln!("Loading config from: {}", config_path.display());
+        }
+        let project_file = std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_project_file(&dir));
+        if let Some(project_file) = project_file.as_ref().filter(|_| verbose) {
+            eprintln!("Merging project config from: {}", project_file.display());
+        }
 
-        let config: Config = config.try_deserialize()?;
-        println!("Loaded workgroup: {:?}", config.aws.workgroup);
+        let config = Self::load_from(&config_path, project_file.as_deref())?;
+        if verbose {
+            eprintln!("Loaded workgroup: {:?}", config.aws.workgroup);
+        }
 
         Ok(config)
     }
+
+    /// Reads the user config at `path`, with the settings of `project_file`
+    /// taking precedence over it
+    fn load_from(path: &Path, project_file: Option<&Path>) -> Result<Self> {
+        let mut builder = config::Config::builder().add_source(config::File::from(path));
+        if let Some(project_file) = project_file {
+            builder = builder.add_source(config::File::from(project_file));
+        }
+
+        let config: Config = builder.build()?.try_deserialize()?;
+
+        if StrftimeItems::new(&config.app.date_format).any(|item| matches!(item, Item::Error)) {
+            anyhow::bail!(
+                "Invalid date_format in {}: {:?}",
+                project_file.unwrap_or(path).display(),
+                config.app.date_format
+            );
+        }
+        Ok(config)
+    }
+
+    /// Writes the config to `path` in the format of its extension (TOML unless
+    /// it ends in .json, .yaml or .yml), creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = match ConfigFormat::from_path(path) {
+            ConfigFormat::Toml => toml::to_string_pretty(self)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(self)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(self)?,
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
 }
 
-fn get_config_path() -> Result<PathBuf> {
+/// File format of the user config, detected from its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::Json,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Toml,
+        }
+    }
+}
+
+/// Config file names looked for in the config directory, in order of
+/// preference. The first one is created when none exists.
+const CONFIG_FILE_NAMES: [&str; 4] = ["config.toml", "config.json", "config.yaml", "config.yml"];
+
+/// Directory holding locally cached query results, next to the config file
+pub fn get_cache_dir() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("cache"))
+}
+
+/// Local log of executed queries, next to the config file
+pub fn get_history_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("history.jsonl"))
+}
+
+fn get_config_dir() -> Result<PathBuf> {
+    let config_path = get_config_path()?;
+    config_path
+        .parent()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))
+}
+
+/// The config file: config.toml, config.json or config.yaml in the config
+/// directory, whichever exists, or config.toml when there is none yet
+pub fn get_config_path() -> Result<PathBuf> {
     // Always use XDG config dir (~/.config/athena-cli/config.toml)
     if let Ok(home) = std::env::var("HOME") {
-        return Ok(PathBuf::from(home).join(".config/athena-cli/config.toml"));
+        return Ok(find_config_file(
+            &PathBuf::from(home).join(".config/athena-cli"),
+        ));
     }
 
     // Fallback only if HOME is not available
     let proj_dirs = ProjectDirs::from("com", "your-org", "athena-cli")
         .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
 
-    Ok(proj_dirs.config_dir().join("config.toml"))
+    Ok(find_config_file(proj_dirs.config_dir()))
+}
+
+/// The first config file in `dir` that exists, or the TOML one
+fn find_config_file(dir: &Path) -> PathBuf {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| dir.join(CONFIG_FILE_NAMES[0]))
+}
+
+/// The nearest project file in `dir` or one of its parents
+fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_project_file_walks_up() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("repo/src/queries");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_file(&nested), None);
+
+        let project_file = root.path().join("repo").join(PROJECT_FILE_NAME);
+        fs::write(&project_file, "[aws]\n").unwrap();
+        assert_eq!(find_project_file(&nested), Some(project_file.clone()));
+        assert_eq!(
+            find_project_file(&root.path().join("repo")),
+            Some(project_file)
+        );
+
+        // The nearest file wins over one further up
+        let closer = nested.join(PROJECT_FILE_NAME);
+        fs::write(&closer, "[aws]\n").unwrap();
+        assert_eq!(find_project_file(&nested), Some(closer));
+    }
+
+    #[test]
+    fn test_find_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_config_file(dir.path()), dir.path().join("config.toml"));
+
+        fs::write(dir.path().join("config.yml"), "aws: {}\n").unwrap();
+        assert_eq!(find_config_file(dir.path()), dir.path().join("config.yml"));
+
+        // TOML wins when there are several
+        fs::write(dir.path().join("config.toml"), "[aws]\n").unwrap();
+        assert_eq!(find_config_file(dir.path()), dir.path().join("config.toml"));
+    }
+
+    #[test]
+    fn test_config_round_trips_in_each_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.aws.database = Some("sales".to_string());
+        config.app.stale_warn_age = Some(Duration::from_secs(1800));
+        config.app.default_format = Some(OutputFormat::Json);
+        config
+            .app
+            .region_price_per_tb
+            .insert("sa-east-1".to_string(), 9.0);
+
+        for (name, format) in [
+            ("config.toml", ConfigFormat::Toml),
+            ("config.json", ConfigFormat::Json),
+            ("config.yaml", ConfigFormat::Yaml),
+        ] {
+            let path = dir.path().join(name);
+            assert_eq!(ConfigFormat::from_path(&path), format);
+            config.save(&path).unwrap();
+
+            let loaded = Config::load_from(&path, None).unwrap();
+            assert_eq!(
+                serde_json::to_value(&loaded).unwrap(),
+                serde_json::to_value(&config).unwrap(),
+                "{} didn't round-trip",
+                name
+            );
+        }
+
+        // Each file is written in its own format
+        let json = fs::read_to_string(dir.path().join("config.json")).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+        let yaml = fs::read_to_string(dir.path().join("config.yaml")).unwrap();
+        assert!(yaml.contains("database: sales"));
+    }
+
+    #[test]
+    fn test_project_file_overrides_user_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = dir.path().join("config.toml");
+        Config::default().save(&user).unwrap();
+
+        let project_file = dir.path().join(PROJECT_FILE_NAME);
+        fs::write(
+            &project_file,
+            "[aws]\nregion = \"us-east-1\"\nworkgroup = \"analytics\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from(&user, None).unwrap();
+        assert_eq!(config.aws.region.as_deref(), Some("eu-west-1"));
+
+        let config = Config::load_from(&user, Some(&project_file)).unwrap();
+        assert_eq!(config.aws.region.as_deref(), Some("us-east-1"));
+        assert_eq!(config.aws.workgroup.as_deref(), Some("analytics"));
+        // Settings the project file leaves out come from the user config
+        assert_eq!(config.aws.catalog.as_deref(), Some("AwsDataCatalog"));
+        assert_eq!(config.app.max_rows, 1000);
+    }
 }
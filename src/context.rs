@@ -1,8 +1,13 @@
 use crate::cli::{AwsArgs, DisplayArgs};
-use crate::config::Config;
+use crate::commands::common::TimestampFormat;
+use crate::config::{Config, OutputFormat};
 use anyhow::Result;
 use std::env;
-use std::sync::Arc;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::OnceCell;
 
 /// Holds all runtime context including config, CLI args, and AWS clients
 pub struct Context {
@@ -10,6 +15,11 @@ pub struct Context {
     aws_args: AwsArgs,
     display_args: DisplayArgs,
     aws_config: Arc<aws_config::SdkConfig>,
+    // Clients are built on first use and shared by every later call
+    athena_client: OnceLock<aws_sdk_athena::Client>,
+    s3_client: OnceLock<aws_sdk_s3::Client>,
+    // Fetched on first use; `None` when it can't be read
+    workgroup_details: OnceCell<Option<aws_sdk_athena::types::WorkGroup>>,
 }
 
 impl Context {
@@ -19,84 +29,396 @@ impl Context {
             aws_args,
             display_args,
             aws_config: Arc::new(aws_config::SdkConfig::builder().build()),
+            athena_client: OnceLock::new(),
+            s3_client: OnceLock::new(),
+            workgroup_details: OnceCell::new(),
         };
 
-        let aws_config = Arc::new(crate::aws::build_aws_config(ctx.profile(), ctx.region()).await?);
+        let aws_config = Arc::new(
+            crate::aws::build_aws_config(ctx.profile(), ctx.region(), ctx.ca_bundle()).await?,
+        );
 
         Ok(Self { aws_config, ..ctx })
     }
 
+    /// A copy of the context that runs queries against `database` and
+    /// `catalog` where given, as if they had been passed as flags
+    pub fn with_query_context(&self, database: Option<String>, catalog: Option<String>) -> Self {
+        let mut aws_args = self.aws_args.clone();
+        aws_args.database = database.or(aws_args.database);
+        aws_args.catalog = catalog.or(aws_args.catalog);
+
+        Self {
+            config: self.config.clone(),
+            aws_args,
+            display_args: self.display_args.clone(),
+            aws_config: Arc::clone(&self.aws_config),
+            athena_client: self.athena_client.clone(),
+            s3_client: self.s3_client.clone(),
+            workgroup_details: self.workgroup_details.clone(),
+        }
+    }
+
     pub fn profile(&self) -> Option<String> {
-        self.aws_args
-            .profile
-            .clone()
-            .or_else(|| env::var("AWS_PROFILE").ok())
-            .or_else(|| env::var("AWS_DEFAULT_PROFILE").ok())
-            .or_else(|| self.config.aws.profile.clone())
+        self.resolve_profile().map(|setting| setting.value)
+    }
+
+    /// The profile and where it was set, `None` for the default credential chain
+    pub fn resolve_profile(&self) -> Option<Setting> {
+        Setting::resolve(
+            ("--profile", self.aws_args.profile.clone()),
+            &["AWS_PROFILE", "AWS_DEFAULT_PROFILE"],
+            self.config.aws.profile.clone(),
+        )
     }
 
     pub fn region(&self) -> String {
-        let region = self
-            .aws_args
-            .region
-            .as_ref()
-            .cloned()
-            .or_else(|| env::var("AWS_REGION").ok())
-            .or_else(|| self.config.aws.region.clone())
-            .unwrap_or_else(|| "eu-west-1".to_string());
+        self.resolve_region().value
+    }
+
+    /// The region and where it was set
+    pub fn resolve_region(&self) -> Setting {
+        Setting::resolve(
+            ("--region", self.aws_args.region.clone()),
+            &["AWS_REGION", "AWS_DEFAULT_REGION"],
+            self.config.aws.region.clone(),
+        )
+        .unwrap_or_else(|| Setting::default_value("eu-west-1"))
+    }
 
-        region
+    /// Extra trusted certificates (PEM) for TLS, e.g. for a proxy or private endpoint
+    pub fn ca_bundle(&self) -> Option<String> {
+        env::var("AWS_CA_BUNDLE")
+            .ok()
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Endpoint override picked up by the SDK from `AWS_ENDPOINT_URL` or the profile
+    pub fn endpoint_url(&self) -> Option<String> {
+        self.aws_config.endpoint_url().map(str::to_string)
     }
 
     pub fn database(&self) -> Option<String> {
-        self.aws_args
-            .database
-            .clone()
-            .or_else(|| env::var("AWS_ATHENA_DATABASE").ok())
-            .or_else(|| self.config.aws.database.clone())
+        self.resolve_database().map(|setting| setting.value)
+    }
+
+    /// The default database and where it was set, `None` when there is none
+    pub fn resolve_database(&self) -> Option<Setting> {
+        Setting::resolve(
+            ("--database", self.aws_args.database.clone()),
+            &["AWS_ATHENA_DATABASE"],
+            self.config.aws.database.clone(),
+        )
     }
 
     pub fn workgroup(&self) -> String {
-        self.aws_args
-            .workgroup
-            .as_ref()
-            .cloned()
-            .or_else(|| env::var("AWS_ATHENA_WORKGROUP").ok())
-            .or_else(|| self.config.aws.workgroup.clone())
-            .unwrap_or_else(|| "primary".to_string())
+        self.resolve_workgroup().value
+    }
+
+    /// The workgroup and where it was set
+    pub fn resolve_workgroup(&self) -> Setting {
+        Setting::resolve(
+            ("--workgroup", self.aws_args.workgroup.clone()),
+            &["AWS_ATHENA_WORKGROUP"],
+            self.config.aws.workgroup.clone(),
+        )
+        .unwrap_or_else(|| Setting::default_value("primary"))
     }
 
     pub fn catalog(&self) -> String {
-        self.aws_args
-            .catalog
-            .as_ref()
-            .cloned()
-            .or_else(|| env::var("AWS_ATHENA_CATALOG").ok())
-            .or_else(|| self.config.aws.catalog.clone())
-            .unwrap_or_else(|| "AwsDataCatalog".to_string())
+        self.resolve_catalog().value
+    }
+
+    /// The data catalog and where it was set
+    pub fn resolve_catalog(&self) -> Setting {
+        Setting::resolve(
+            ("--catalog", self.aws_args.catalog.clone()),
+            &["AWS_ATHENA_CATALOG"],
+            self.config.aws.catalog.clone(),
+        )
+        .unwrap_or_else(|| Setting::default_value("AwsDataCatalog"))
     }
 
     pub fn output_location(&self) -> Option<String> {
-        self.aws_args
-            .output_location
-            .clone()
-            .or_else(|| env::var("AWS_ATHENA_OUTPUT_LOCATION").ok())
-            .or(Some(self.config.aws.output_location.clone()))
+        self.resolve_output_location().map(|setting| setting.value)
     }
 
-    pub fn aws_config(&self) -> &aws_config::SdkConfig {
-        &self.aws_config
+    /// The result location and where it was set. `None` when no location is set,
+    /// in which case Athena falls back to the workgroup's own setting.
+    pub fn resolve_output_location(&self) -> Option<Setting> {
+        Setting::resolve(
+            ("--output-location", self.aws_args.output_location.clone()),
+            &["AWS_ATHENA_OUTPUT_LOCATION"],
+            Some(self.config.aws.output_location.clone()),
+        )
+        .filter(|setting| !setting.value.is_empty())
     }
 
+    /// Athena client for the resolved config. Clients are cheap to clone and
+    /// share one connection pool, so repeated calls reuse the same client.
     pub fn create_athena_client(&self) -> aws_sdk_athena::Client {
-        aws_sdk_athena::Client::new(&self.aws_config)
+        self.athena_client
+            .get_or_init(|| aws_sdk_athena::Client::new(&self.aws_config))
+            .clone()
+    }
+
+    /// S3 client for the resolved config, shared like [`Self::create_athena_client`]
+    pub fn create_s3_client(&self) -> aws_sdk_s3::Client {
+        self.s3_client
+            .get_or_init(|| aws_sdk_s3::Client::new(&self.aws_config))
+            .clone()
+    }
+
+    /// The current workgroup's settings, fetched once and shared by every later
+    /// call. `None` when they can't be read, e.g. without athena:GetWorkGroup.
+    pub async fn workgroup_details(&self) -> Option<&aws_sdk_athena::types::WorkGroup> {
+        self.workgroup_details
+            .get_or_init(|| async {
+                self.create_athena_client()
+                    .get_work_group()
+                    .work_group(self.workgroup())
+                    .send()
+                    .await
+                    .ok()
+                    .and_then(|result| result.work_group().cloned())
+            })
+            .await
+            .as_ref()
+    }
+
+    pub fn create_sts_client(&self) -> aws_sdk_sts::Client {
+        aws_sdk_sts::Client::new(&self.aws_config)
+    }
+
+    pub fn create_cloudwatch_client(&self) -> aws_sdk_cloudwatch::Client {
+        aws_sdk_cloudwatch::Client::new(&self.aws_config)
     }
 
     pub fn quiet(&self) -> bool {
         self.display_args.quiet
     }
 
+    /// Verbosity level: 0 is normal output, 1 adds details, 2 adds debug diagnostics
+    pub fn verbosity(&self) -> u8 {
+        if self.quiet() {
+            0
+        } else {
+            self.display_args.verbose
+        }
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbosity() >= 1
+    }
+
+    pub fn debug(&self) -> bool {
+        self.verbosity() >= 2
+    }
+
+    pub fn max_rows(&self) -> usize {
+        self.config.app.max_rows
+    }
+
+    /// Age past which reused query results are reported as stale, if configured
+    pub fn stale_warn_age(&self) -> Option<Duration> {
+        self.config.app.stale_warn_age
+    }
+
     pub fn history_size(&self) -> i32 {
         self.config.app.history_size
     }
+
+    /// Row limit added to queries without one, if configured
+    pub fn auto_limit(&self) -> Option<u64> {
+        self.config.app.auto_limit.filter(|&limit| limit > 0)
+    }
+
+    /// Price per TB scanned in the current region, for cost estimates
+    pub fn price_per_tb(&self) -> f64 {
+        self.config
+            .app
+            .region_price_per_tb
+            .get(&self.region())
+            .copied()
+            .unwrap_or(self.config.app.price_per_tb)
+    }
+
+    /// Widest value shown in a table column, 0 for no limit
+    pub fn max_column_width(&self) -> usize {
+        self.display_args
+            .max_col_width
+            .unwrap_or(self.config.app.max_column_width)
+    }
+
+    /// Directory of the local .sql query library, if configured
+    pub fn query_library_dir(&self) -> Option<PathBuf> {
+        self.config.app.query_library_dir.clone()
+    }
+
+    pub fn timestamp_format(&self) -> TimestampFormat {
+        TimestampFormat {
+            format: self.config.app.date_format.clone(),
+            timezone: self.config.app.timezone,
+        }
+    }
+
+    /// Format of `query` results, given the --format flag
+    pub fn query_format(&self, flag: Option<OutputFormat>) -> OutputFormat {
+        resolve_format(
+            flag,
+            self.config.app.query.format,
+            self.config.app.default_format,
+        )
+    }
+
+    /// Format of the `history` listing, given the --format flag
+    pub fn history_format(&self, flag: Option<OutputFormat>) -> OutputFormat {
+        resolve_format(
+            flag,
+            self.config.app.history.format,
+            self.config.app.default_format,
+        )
+    }
+}
+
+/// A command's output format: the flag, then the command's own config, then
+/// app.default_format, then a table
+fn resolve_format(
+    flag: Option<OutputFormat>,
+    command: Option<OutputFormat>,
+    default: Option<OutputFormat>,
+) -> OutputFormat {
+    flag.or(command).or(default).unwrap_or_default()
+}
+
+/// Where a resolved setting's value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingSource {
+    /// A command-line flag, e.g. `--workgroup`
+    Flag(&'static str),
+    /// An environment variable, e.g. `AWS_ATHENA_WORKGROUP`
+    Env(&'static str),
+    /// The config file
+    Config,
+    /// The built-in default
+    Default,
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flag(flag) => write!(f, "flag {}", flag),
+            Self::Env(var) => write!(f, "env {}", var),
+            Self::Config => write!(f, "config"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// A setting's effective value together with where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub value: String,
+    pub source: SettingSource,
+}
+
+impl Setting {
+    /// Applies the precedence shared by all AWS settings: the flag wins, then the
+    /// first environment variable that is set, then the config file
+    fn resolve(
+        flag: (&'static str, Option<String>),
+        env_vars: &[&'static str],
+        config: Option<String>,
+    ) -> Option<Self> {
+        let (flag_name, flag_value) = flag;
+        if let Some(value) = flag_value {
+            return Some(Self {
+                value,
+                source: SettingSource::Flag(flag_name),
+            });
+        }
+
+        env_vars
+            .iter()
+            .find_map(|&var| {
+                env::var(var).ok().map(|value| Self {
+                    value,
+                    source: SettingSource::Env(var),
+                })
+            })
+            .or_else(|| {
+                config.map(|value| Self {
+                    value,
+                    source: SettingSource::Config,
+                })
+            })
+    }
+
+    fn default_value(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            source: SettingSource::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNSET_VAR: &str = "ATHENA_CLI_TEST_UNSET_VARIABLE";
+
+    #[test]
+    fn test_flag_wins_over_config() {
+        let setting = Setting::resolve(
+            ("--workgroup", Some("from-flag".to_string())),
+            &[UNSET_VAR],
+            Some("from-config".to_string()),
+        )
+        .unwrap();
+        assert_eq!(setting.value, "from-flag");
+        assert_eq!(setting.source, SettingSource::Flag("--workgroup"));
+    }
+
+    #[test]
+    fn test_config_used_without_flag_or_env() {
+        let setting = Setting::resolve(
+            ("--workgroup", None),
+            &[UNSET_VAR],
+            Some("from-config".to_string()),
+        )
+        .unwrap();
+        assert_eq!(setting.value, "from-config");
+        assert_eq!(setting.source, SettingSource::Config);
+    }
+
+    #[test]
+    fn test_unset_everywhere_resolves_to_none() {
+        assert_eq!(
+            Setting::resolve(("--database", None), &[UNSET_VAR], None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_precedence() {
+        use OutputFormat::*;
+
+        assert_eq!(resolve_format(Some(Csv), Some(Json), Some(Parquet)), Csv);
+        assert_eq!(resolve_format(None, Some(Json), Some(Parquet)), Json);
+        assert_eq!(resolve_format(None, None, Some(Parquet)), Parquet);
+        assert_eq!(resolve_format(None, None, None), Table);
+        assert_eq!(resolve_format(Some(Table), Some(Json), None), Table);
+    }
+
+    #[test]
+    fn test_source_display() {
+        assert_eq!(SettingSource::Flag("--region").to_string(), "flag --region");
+        assert_eq!(
+            SettingSource::Env("AWS_REGION").to_string(),
+            "env AWS_REGION"
+        );
+        assert_eq!(SettingSource::Config.to_string(), "config");
+        assert_eq!(SettingSource::Default.to_string(), "default");
+    }
 }
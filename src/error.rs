@@ -0,0 +1,217 @@
+//! Error categories shared by all commands.
+//!
+//! Commands still return `anyhow::Result`; the places that know why something
+//! failed wrap the cause in an [`AthenaCliError`], and `main` finds it in the
+//! error chain instead of matching on message text.
+
+use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_types::error::display::DisplayErrorContext;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AthenaCliError {
+    /// The query or one of its arguments was rejected
+    #[error("{0}")]
+    Validation(String),
+    /// Credentials are missing, expired or not allowed to make the request
+    #[error("{0}")]
+    Auth(String),
+    /// A query execution, table, database or object doesn't exist
+    #[error("{0}")]
+    NotFound(String),
+    /// AWS refused the request because of rate limits
+    #[error("{0}")]
+    Throttled(String),
+    /// The query ran but failed or was cancelled
+    #[error("{0}")]
+    QueryFailed(String),
+    /// The query succeeded but its results failed a check such as --fail-empty
+    #[error("{0}")]
+    AssertionFailed(String),
+    /// Any other S3 failure
+    #[error("{0}")]
+    S3(String),
+    /// Any other AWS failure
+    #[error("{0}")]
+    Aws(String),
+}
+
+impl AthenaCliError {
+    /// Categorizes an error returned by an Athena, Glue or STS client
+    pub fn from_sdk<E, R>(err: SdkError<E, R>) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+        R: Debug,
+    {
+        Self::categorize(&err, Self::Aws)
+    }
+
+    /// Categorizes an error returned by the S3 client
+    pub fn from_s3<E, R>(err: SdkError<E, R>) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+        R: Debug,
+    {
+        Self::categorize(&err, Self::S3)
+    }
+
+    fn categorize<E, R>(err: &SdkError<E, R>, other: fn(String) -> Self) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+        R: Debug,
+    {
+        match (err.code(), err.message()) {
+            (Some(code), Some(message)) => {
+                Self::from_code(code, format!("{}: {}", code, message), other)
+            }
+            (Some(code), None) => Self::from_code(code, code.to_string(), other),
+            (None, _) => {
+                let message = DisplayErrorContext(err).to_string();
+                // Requests that never reached AWS, most often because no credentials were found
+                if message.contains("credentials") {
+                    Self::Auth(message)
+                } else {
+                    other(message)
+                }
+            }
+        }
+    }
+
+    /// Category of an AWS error code, or `other` when the code isn't one we know
+    pub fn from_code(code: &str, message: String, other: fn(String) -> Self) -> Self {
+        match code {
+            "AccessDenied"
+            | "AccessDeniedException"
+            | "ExpiredToken"
+            | "ExpiredTokenException"
+            | "UnrecognizedClientException"
+            | "InvalidClientTokenId"
+            | "InvalidSignatureException"
+            | "SignatureDoesNotMatch"
+            | "ForbiddenException" => Self::Auth(message),
+            "Throttling"
+            | "ThrottlingException"
+            | "TooManyRequestsException"
+            | "RequestLimitExceeded"
+            | "SlowDown" => Self::Throttled(message),
+            "ResourceNotFoundException"
+            | "EntityNotFoundException"
+            | "NoSuchKey"
+            | "NoSuchBucket"
+            | "NotFound" => Self::NotFound(message),
+            "InvalidRequestException" | "ValidationException" => Self::Validation(message),
+            _ => other(message),
+        }
+    }
+
+    /// The first `AthenaCliError` in an error's chain of causes
+    pub fn find(err: &anyhow::Error) -> Option<&Self> {
+        err.chain().find_map(|cause| cause.downcast_ref::<Self>())
+    }
+
+    /// Process exit code for this category. These are part of the CLI's
+    /// interface (see the README), so existing codes must not change.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Validation(_) => 2,
+            Self::Auth(_) => 3,
+            Self::NotFound(_) => 4,
+            Self::QueryFailed(_) => 5,
+            Self::Throttled(_) => 6,
+            Self::AssertionFailed(_) => 7,
+            Self::S3(_) | Self::Aws(_) => 1,
+        }
+    }
+}
+
+/// Exit code for an error returned from a command, 1 when it isn't categorized
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    AthenaCliError::find(err).map_or(1, AthenaCliError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::operation::get_object::GetObjectError;
+    use aws_smithy_types::error::ErrorMetadata;
+
+    fn service_error(code: &str, message: &str) -> SdkError<GetObjectError, ()> {
+        let meta = ErrorMetadata::builder().code(code).message(message).build();
+        SdkError::service_error(GetObjectError::generic(meta), ())
+    }
+
+    #[test]
+    fn test_from_code() {
+        let category =
+            |code| match AthenaCliError::from_code(code, String::new(), AthenaCliError::Aws) {
+                AthenaCliError::Validation(_) => "validation",
+                AthenaCliError::Auth(_) => "auth",
+                AthenaCliError::NotFound(_) => "not found",
+                AthenaCliError::Throttled(_) => "throttled",
+                AthenaCliError::QueryFailed(_) => "query failed",
+                AthenaCliError::AssertionFailed(_) => "assertion failed",
+                AthenaCliError::S3(_) => "s3",
+                AthenaCliError::Aws(_) => "aws",
+            };
+
+        assert_eq!(category("AccessDeniedException"), "auth");
+        assert_eq!(category("ExpiredTokenException"), "auth");
+        assert_eq!(category("UnrecognizedClientException"), "auth");
+        assert_eq!(category("ThrottlingException"), "throttled");
+        assert_eq!(category("SlowDown"), "throttled");
+        assert_eq!(category("ResourceNotFoundException"), "not found");
+        assert_eq!(category("NoSuchKey"), "not found");
+        assert_eq!(category("InvalidRequestException"), "validation");
+        assert_eq!(category("InternalServerException"), "aws");
+    }
+
+    #[test]
+    fn test_from_sdk_error() {
+        let err = AthenaCliError::from_s3(service_error("NoSuchKey", "The key does not exist"));
+        assert!(matches!(err, AthenaCliError::NotFound(_)));
+        assert_eq!(err.to_string(), "NoSuchKey: The key does not exist");
+
+        let err = AthenaCliError::from_s3(service_error("AccessDenied", "Access Denied"));
+        assert!(matches!(err, AthenaCliError::Auth(_)));
+
+        let err = AthenaCliError::from_s3(service_error("InternalError", "Try again"));
+        assert!(matches!(err, AthenaCliError::S3(_)));
+
+        let err = AthenaCliError::from_sdk(service_error("InternalError", "Try again"));
+        assert!(matches!(err, AthenaCliError::Aws(_)));
+
+        let missing: SdkError<GetObjectError, ()> =
+            SdkError::construction_failure("no credentials in the provider chain");
+        assert!(matches!(
+            AthenaCliError::from_sdk(missing),
+            AthenaCliError::Auth(_)
+        ));
+    }
+
+    #[test]
+    fn test_find_in_chain() {
+        let err = anyhow::Error::new(AthenaCliError::Throttled("slow down".to_string()))
+            .context("Failed to start query");
+        assert!(matches!(
+            AthenaCliError::find(&err),
+            Some(AthenaCliError::Throttled(_))
+        ));
+        assert!(AthenaCliError::find(&anyhow::anyhow!("plain")).is_none());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let code = |err: AthenaCliError| exit_code(&anyhow::Error::new(err).context("context"));
+
+        assert_eq!(code(AthenaCliError::Validation(String::new())), 2);
+        assert_eq!(code(AthenaCliError::Auth(String::new())), 3);
+        assert_eq!(code(AthenaCliError::NotFound(String::new())), 4);
+        assert_eq!(code(AthenaCliError::QueryFailed(String::new())), 5);
+        assert_eq!(code(AthenaCliError::Throttled(String::new())), 6);
+        assert_eq!(code(AthenaCliError::AssertionFailed(String::new())), 7);
+        assert_eq!(code(AthenaCliError::S3(String::new())), 1);
+        assert_eq!(exit_code(&anyhow::anyhow!("uncategorized")), 1);
+    }
+}
@@ -1,7 +1,10 @@
 pub mod aws;
+pub mod cache;
 pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod context;
+pub mod error;
+pub mod local_history;
 pub mod utils;
 pub mod validation;
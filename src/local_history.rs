@@ -0,0 +1,108 @@
+//! Durable local log of queries run through the CLI, kept as JSON Lines next to
+//! the config file. Unlike Athena's own history it spans workgroups and never expires.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One executed query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub query_execution_id: String,
+    pub query: String,
+    pub workgroup: String,
+    /// When the query finished, as an RFC 3339 timestamp
+    pub timestamp: String,
+    pub state: String,
+    pub data_scanned: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalHistory {
+    path: PathBuf,
+}
+
+impl LocalHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Opens the history log under the config directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::new(crate::config::get_history_path()?))
+    }
+
+    /// Appends an entry to the end of the log
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!(
+                "Failed to open history log: {}",
+                self.path.display()
+            ))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+        Ok(())
+    }
+
+    /// Reads all entries, oldest first. Lines that can't be parsed are skipped.
+    pub fn read(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path).context(format!(
+            "Failed to read history log: {}",
+            self.path.display()
+        ))?;
+
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> HistoryEntry {
+        HistoryEntry {
+            query_execution_id: id.to_string(),
+            query: "SELECT 1".to_string(),
+            workgroup: "primary".to_string(),
+            timestamp: "2025-05-01T12:00:00+00:00".to_string(),
+            state: "SUCCEEDED".to_string(),
+            data_scanned: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_local_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = LocalHistory::new(dir.path().join("athena-cli").join("history.jsonl"));
+
+        assert!(history.read().unwrap().is_empty());
+
+        history.append(&entry("first")).unwrap();
+        history.append(&entry("second")).unwrap();
+
+        // Corrupt lines don't hide the rest of the log
+        let mut file = OpenOptions::new().append(true).open(&history.path).unwrap();
+        writeln!(file, "not json").unwrap();
+
+        assert_eq!(
+            history.read().unwrap(),
+            vec![entry("first"), entry("second")]
+        );
+    }
+}
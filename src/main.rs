@@ -1,19 +1,63 @@
 mod aws;
+mod cache;
 mod cli;
 mod commands;
 mod config;
 mod context;
+mod error;
+mod local_history;
 mod utils;
 mod validation;
 
 use anyhow::Result;
 use clap::Parser;
 use context::Context;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // Result tables shorten long values themselves (see --max-col-width), so
+    // polars' own string limit is lifted. The environment is only safe to change
+    // before the runtime starts its threads.
+    std::env::set_var("POLARS_FMT_STR_LEN", "-1");
+    start()
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn start() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(error::exit_code(&err))
+        }
+    }
+}
+
+async fn run() -> Result<()> {
     let cli = cli::Cli::parse();
-    let config = config::Config::load()?;
+    utils::color::configure(cli.display.color);
+    utils::pager::configure(cli.display.no_pager);
+
+    // Completion scripts need neither config nor AWS credentials
+    if let cli::Commands::Completion(args) = &cli.command {
+        return commands::completion::generate(args);
+    }
+
+    // Managing local state doesn't need the client context either. config init
+    // only calls AWS to offer names to pick from, and works without credentials.
+    if let cli::Commands::Config { command } = &cli.command {
+        match command {
+            cli::ConfigCommands::Init(args) => return commands::config::init(args, &cli.aws).await,
+            cli::ConfigCommands::Cache { command } => match command {
+                cli::CacheCommands::Clear => return commands::config::clear_cache(),
+            },
+            // Resolving settings needs the loaded config and flags, like any command
+            cli::ConfigCommands::Resolve => {}
+        }
+    }
+
+    let config = config::Config::load(cli.display.verbose > 0)?;
+    utils::table_style::configure(cli.display.table_style.unwrap_or(config.app.table_style));
 
     // Create global context
     let ctx = Context::new(config, cli.aws, cli.display).await?;
@@ -21,6 +65,7 @@ async fn main() -> Result<()> {
     // Execute command with context
     let result = match &cli.command {
         cli::Commands::Query(args) => commands::query::execute(&ctx, args).await,
+        cli::Commands::Explain(args) => commands::explain::explain(&ctx, args).await,
         cli::Commands::Database { command } => match command {
             cli::DatabaseCommands::List(args) => commands::database::list(&ctx, args).await,
         },
@@ -35,7 +80,39 @@ async fn main() -> Result<()> {
         },
         cli::Commands::History(args) => commands::history::list(&ctx, args).await,
         cli::Commands::Inspect(args) => commands::inspect::inspect(&ctx, args).await,
+        cli::Commands::Rerun(args) => commands::rerun::rerun(&ctx, args).await,
         cli::Commands::Download(args) => commands::inspect::download(&ctx, args).await,
+        cli::Commands::Cancel(args) => commands::cancel::cancel(&ctx, args).await,
+        cli::Commands::Cleanup(args) => commands::cleanup::cleanup(&ctx, args).await,
+        cli::Commands::Metrics(args) => commands::metrics::metrics(&ctx, args).await,
+        cli::Commands::Repl(args) => commands::repl::run(&ctx, args).await,
+        cli::Commands::Prepare(args) => commands::prepared_statement::prepare(&ctx, args).await,
+        cli::Commands::PreparedStatement { command } => match command {
+            cli::PreparedStatementCommands::List => commands::prepared_statement::list(&ctx).await,
+            cli::PreparedStatementCommands::Delete(args) => {
+                commands::prepared_statement::delete(&ctx, args).await
+            }
+        },
+        cli::Commands::Library { command } => match command {
+            cli::LibraryCommands::List(args) => commands::library::list(&ctx, args),
+        },
+        cli::Commands::NamedQuery { command } => match command {
+            cli::NamedQueryCommands::List => commands::named_query::list(&ctx).await,
+            cli::NamedQueryCommands::Show(args) => commands::named_query::show(&ctx, args).await,
+            cli::NamedQueryCommands::Create(args) => {
+                commands::named_query::create(&ctx, args).await
+            }
+            cli::NamedQueryCommands::Delete(args) => {
+                commands::named_query::delete(&ctx, args).await
+            }
+        },
+        cli::Commands::Whoami => commands::whoami::whoami(&ctx).await,
+        cli::Commands::Config {
+            command: cli::ConfigCommands::Resolve,
+        } => commands::config::resolve(&ctx),
+        cli::Commands::Completion(_) | cli::Commands::Config { .. } => {
+            unreachable!("handled before context creation")
+        }
     };
 
     // Handle credential errors
@@ -0,0 +1,38 @@
+use crate::cli::ColorChoice;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Decide whether output should be colorized for the given choice and environment
+pub fn should_colorize(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color && is_terminal,
+    }
+}
+
+/// Apply the color choice globally; must run before any command renders output
+pub fn configure(choice: ColorChoice) {
+    // https://no-color.org: any non-empty value disables color
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = should_colorize(choice, no_color, io::stdout().is_terminal());
+
+    colored::control::set_override(enabled);
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_colorize() {
+        assert!(should_colorize(ColorChoice::Always, true, false));
+        assert!(!should_colorize(ColorChoice::Never, false, true));
+
+        assert!(should_colorize(ColorChoice::Auto, false, true));
+        assert!(!should_colorize(ColorChoice::Auto, true, true));
+        assert!(!should_colorize(ColorChoice::Auto, false, false));
+    }
+}
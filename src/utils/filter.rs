@@ -1,48 +1,85 @@
 /// Utility functions for filtering collections based on patterns
-pub fn matches_pattern<T: AsRef<str>>(value: T, pattern: &str) -> bool {
+///
+/// Patterns containing `*` (any run of characters) or `?` (exactly one character)
+/// are matched as globs against the whole value. Patterns without wildcards fall
+/// back to substring matching. Matching ignores case unless `case_sensitive` is set.
+pub fn matches_pattern<T: AsRef<str>>(value: T, pattern: &str, case_sensitive: bool) -> bool {
     let value = value.as_ref();
 
-    // Simple wildcard matching
-    if pattern.contains('*') {
-        let parts: Vec<&str> = pattern.split('*').collect();
-
-        // Handle prefix matching (pattern ends with *)
-        if pattern.ends_with('*') && parts.len() == 2 {
-            return value.starts_with(parts[0]);
-        }
-
-        // Handle suffix matching (pattern starts with *)
-        if pattern.starts_with('*') && parts.len() == 2 {
-            return value.ends_with(parts[1]);
-        }
+    if case_sensitive {
+        matches_normalized(value, pattern)
+    } else {
+        matches_normalized(&value.to_lowercase(), &pattern.to_lowercase())
+    }
+}
 
-        // Handle contains matching (pattern is *text*)
-        if pattern.starts_with('*') && pattern.ends_with('*') && parts.len() == 3 {
-            return value.contains(parts[1]);
-        }
+fn matches_normalized(value: &str, pattern: &str) -> bool {
+    if pattern.contains(['*', '?']) {
+        glob_match(value, pattern)
     } else {
         // Default to substring matching instead of exact matching
-        return value.to_lowercase().contains(&pattern.to_lowercase());
+        value.contains(pattern)
     }
+}
 
-    // Exact matching (only reached if none of the wildcard patterns matched)
-    value == pattern
+/// Match `value` against a glob `pattern` supporting `*` and `?`
+fn glob_match(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    let (mut v, mut p) = (0, 0);
+    // Position in the pattern just after the last `*`, and the value position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            v += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p + 1, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            backtrack = Some((star_p, star_v + 1));
+            p = star_p;
+            v = star_v + 1;
+        } else {
+            return false;
+        }
+    }
+
+    // Any remaining pattern must be trailing `*`s
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /// Filter a collection of items based on a pattern
-pub fn filter_items<'a, T, F>(items: &'a [T], pattern: Option<&str>, extractor: F) -> Vec<&'a T>
+pub fn filter_items<'a, T, F>(
+    items: &'a [T],
+    pattern: Option<&str>,
+    case_sensitive: bool,
+    extractor: F,
+) -> Vec<&'a T>
 where
     F: Fn(&T) -> &str,
 {
     match pattern {
         Some(pattern) => items
             .iter()
-            .filter(|item| matches_pattern(extractor(item), pattern))
+            .filter(|item| matches_pattern(extractor(item), pattern, case_sensitive))
             .collect(),
         None => items.iter().collect(),
     }
 }
 
+/// Parses a `column=text` result filter, as given to `query --where-contains`.
+/// The text may be empty or contain `=`; only the column name is required.
+pub fn parse_column_filter(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((column, text)) if !column.is_empty() => Ok((column.to_string(), text.to_string())),
+        _ => Err(format!("expected column=text, got '{}'", arg)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,22 +95,59 @@ mod tests {
     #[test]
     fn test_matches_pattern() {
         // Test exact match
-        assert!(matches_pattern("hello", "hello"));
+        assert!(matches_pattern("hello", "hello", false));
 
         // Test substring match (should match if we updated the function)
-        assert!(matches_pattern("hello world", "hello"));
+        assert!(matches_pattern("hello world", "hello", false));
 
         // Test case insensitivity
-        assert!(matches_pattern("Hello World", "hello"));
+        assert!(matches_pattern("Hello World", "hello", false));
 
         // Test wildcard patterns
-        assert!(matches_pattern("hello world", "hello*"));
-        assert!(matches_pattern("hello world", "*world"));
-        assert!(matches_pattern("hello world", "*lo wor*"));
+        assert!(matches_pattern("hello world", "hello*", false));
+        assert!(matches_pattern("hello world", "*world", false));
+        assert!(matches_pattern("hello world", "*lo wor*", false));
 
         // Test non-matches
-        assert!(!matches_pattern("hello", "world"));
-        assert!(!matches_pattern("hello", "hello world"));
+        assert!(!matches_pattern("hello", "world", false));
+        assert!(!matches_pattern("hello", "hello world", false));
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        // Substring matching
+        assert!(matches_pattern("Orders", "orders", false));
+        assert!(!matches_pattern("Orders", "orders", true));
+        assert!(matches_pattern("Orders", "Ord", true));
+
+        // Glob matching
+        assert!(matches_pattern("Orders_2024", "orders_*", false));
+        assert!(!matches_pattern("Orders_2024", "orders_*", true));
+        assert!(matches_pattern("Orders_2024", "Orders_*", true));
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        // Multiple wildcards
+        assert!(matches_pattern("pp_sales_2024", "pp_*_2024", false));
+        assert!(matches_pattern("pp_sales_eu_2024", "pp_*_*_2024", false));
+        assert!(!matches_pattern("pp_sales_2023", "pp_*_2024", false));
+        assert!(matches_pattern("a_b_c", "*_*", false));
+
+        // Single character wildcard
+        assert!(matches_pattern("table", "t?ble", false));
+        assert!(matches_pattern("user_1", "user_?", false));
+        assert!(!matches_pattern("user_12", "user_?", false));
+        assert!(!matches_pattern("user_", "user_?", false));
+
+        // Mixed wildcards
+        assert!(matches_pattern("logs_2024_01", "logs_20??_*", false));
+        assert!(!matches_pattern("logs_24_01", "logs_20??_*", false));
+
+        // Wildcard patterns match the whole value
+        assert!(!matches_pattern("my_events", "events*", false));
+        assert!(matches_pattern("anything", "*", false));
+        assert!(matches_pattern("", "*", false));
     }
 
     #[test]
@@ -103,28 +177,52 @@ mod tests {
         ];
 
         // Test filtering by name
-        let filtered = filter_items(&items, Some("event"), |item| &item.name);
+        let filtered = filter_items(&items, Some("event"), false, |item| &item.name);
         assert_eq!(filtered.len(), 2);
         assert!(filtered.contains(&&items[1])); // UserEvents
         assert!(filtered.contains(&&items[2])); // EventLog
 
         // Test filtering by category
-        let filtered = filter_items(&items, Some("events"), |item| &item.category);
+        let filtered = filter_items(&items, Some("events"), false, |item| &item.category);
         assert_eq!(filtered.len(), 2);
         assert!(filtered.contains(&&items[1])); // UserEvents
         assert!(filtered.contains(&&items[2])); // EventLog
 
         // Test filtering with wildcard
-        let filtered = filter_items(&items, Some("*Log"), |item| &item.name);
+        let filtered = filter_items(&items, Some("*Log"), false, |item| &item.name);
         assert_eq!(filtered.len(), 1);
         assert!(filtered.contains(&&items[2])); // EventLog
 
         // Test filtering with no matches
-        let filtered = filter_items(&items, Some("NonExistent"), |item| &item.name);
+        let filtered = filter_items(&items, Some("NonExistent"), false, |item| &item.name);
         assert_eq!(filtered.len(), 0);
 
         // Test with None pattern (should return all items)
-        let filtered = filter_items(&items, None, |item| &item.name);
+        let filtered = filter_items(&items, None, false, |item| &item.name);
         assert_eq!(filtered.len(), items.len());
+
+        // Test case-sensitive filtering
+        let filtered = filter_items(&items, Some("event"), true, |item| &item.name);
+        assert_eq!(filtered.len(), 0);
+        let filtered = filter_items(&items, Some("Event"), true, |item| &item.name);
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_column_filter() {
+        assert_eq!(
+            parse_column_filter("status=failed"),
+            Ok(("status".to_string(), "failed".to_string()))
+        );
+        assert_eq!(
+            parse_column_filter("url=a=b"),
+            Ok(("url".to_string(), "a=b".to_string()))
+        );
+        assert_eq!(
+            parse_column_filter("note="),
+            Ok(("note".to_string(), String::new()))
+        );
+        assert!(parse_column_filter("status").is_err());
+        assert!(parse_column_filter("=failed").is_err());
     }
 }
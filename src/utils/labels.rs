@@ -0,0 +1,76 @@
+//! `--label key=value` metadata attached to queries as leading SQL comments.
+//!
+//! Athena has no tags on query executions, but it keeps the query text, so a
+//! `-- tag:team=analytics` line makes each execution in the history
+//! self-describing. `history --grep tag:team=analytics` finds them again.
+
+/// Prefix of the comment line written for each label
+const LABEL_PREFIX: &str = "-- tag:";
+
+/// Parses a `key=value` label from the command line
+pub fn parse_label(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", arg))?;
+
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "label names may only contain letters, digits, '_', '-' and '.', got '{}'",
+            key
+        ));
+    }
+    // The comment ends at the line break, so the rest would become SQL
+    if value.contains(['\n', '\r']) {
+        return Err(format!("label values can't contain line breaks: '{}'", key));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Puts one `-- tag:key=value` comment line per label in front of the query
+pub fn prepend_labels(query: &str, labels: &[(String, String)]) -> String {
+    let mut labeled = String::new();
+    for (key, value) in labels {
+        labeled.push_str(&format!("{}{}={}\n", LABEL_PREFIX, key, value));
+    }
+    labeled.push_str(query);
+    labeled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(
+            parse_label("team=analytics").unwrap(),
+            ("team".to_string(), "analytics".to_string())
+        );
+        assert_eq!(
+            parse_label("cost-center=a=b").unwrap(),
+            ("cost-center".to_string(), "a=b".to_string())
+        );
+        assert!(parse_label("team").is_err());
+        assert!(parse_label("=analytics").is_err());
+        assert!(parse_label("my team=analytics").is_err());
+        assert!(parse_label("team=a\nDROP TABLE t").is_err());
+    }
+
+    #[test]
+    fn test_prepend_labels() {
+        let labels = vec![
+            ("team".to_string(), "analytics".to_string()),
+            ("env".to_string(), "prod".to_string()),
+        ];
+        assert_eq!(
+            prepend_labels("SELECT 1", &labels),
+            "-- tag:team=analytics\n-- tag:env=prod\nSELECT 1"
+        );
+        assert_eq!(prepend_labels("SELECT 1", &[]), "SELECT 1");
+    }
+}
@@ -1 +1,8 @@
+pub mod color;
 pub mod filter;
+pub mod labels;
+pub mod pager;
+pub mod params;
+pub mod row_assertion;
+pub mod suggest;
+pub mod table_style;
@@ -0,0 +1,124 @@
+use super::table_style;
+use prettytable::Table;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Pager used when `PAGER` isn't set. `-S` chops long lines instead of
+/// wrapping them, so wide tables stay readable and scroll sideways.
+const DEFAULT_PAGER: &str = "less -S";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Apply `--no-pager` globally; must run before any command renders output
+pub fn configure(no_pager: bool) {
+    ENABLED.store(!no_pager, Ordering::Relaxed);
+}
+
+/// The pager command to run for the value of `PAGER`, or `None` when paging is
+/// turned off with an empty `PAGER` or `PAGER=cat`
+pub fn pager_command(pager_env: Option<&str>) -> Option<&str> {
+    match pager_env.map(str::trim) {
+        None => Some(DEFAULT_PAGER),
+        Some("") | Some("cat") => None,
+        Some(pager) => Some(pager),
+    }
+}
+
+/// Whether `text` is taller or wider than a terminal of `rows` x `columns`
+pub fn exceeds_screen(text: &str, rows: usize, columns: usize) -> bool {
+    // Leave a line for the shell prompt that follows the output
+    text.lines().count() >= rows
+        || text
+            .lines()
+            .any(|line| console::measure_text_width(line) > columns)
+}
+
+/// Prints `text` to stdout, through a pager when stdout is a terminal and the
+/// text doesn't fit on the screen, the way git does. Falls back to printing
+/// directly if the pager can't be started.
+pub fn page(text: &str) -> io::Result<()> {
+    let text = if text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    };
+
+    if !send_to_pager(&text)? {
+        io::stdout().lock().write_all(text.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Prints a table like [`page`], in the configured table style. A table that
+/// fits on the screen is printed with its styles; the pager gets it as plain text.
+pub fn page_table(table: &Table) -> io::Result<()> {
+    let table = table_style::styled(table);
+    if !send_to_pager(&table.to_string())? {
+        table.printstd();
+    }
+    Ok(())
+}
+
+/// Shows `text` in the pager if it should be paged, returning whether it was
+fn send_to_pager(text: &str) -> io::Result<bool> {
+    let fits = match console::Term::stdout().size_checked() {
+        Some((rows, columns)) => !exceeds_screen(text, rows.into(), columns.into()),
+        None => true,
+    };
+    let pager_env = env::var("PAGER").ok();
+    let command = pager_command(pager_env.as_deref())
+        .filter(|_| ENABLED.load(Ordering::Relaxed) && io::stdout().is_terminal() && !fits);
+
+    let Some(mut child) = command.and_then(spawn_pager) else {
+        return Ok(false);
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        match stdin.write_all(text.as_bytes()) {
+            // The pager was closed before reading everything
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            result => result?,
+        }
+    }
+    child.wait()?;
+    Ok(true)
+}
+
+fn spawn_pager(command: &str) -> Option<std::process::Child> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next()?;
+
+    let mut pager = Command::new(program);
+    pager.args(parts).stdin(Stdio::piped());
+    // Like git: quit if the output fits after all, pass colors through, keep the
+    // output on screen after quitting. An explicit LESS setting wins.
+    if env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+
+    pager.spawn().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), Some("less -S"));
+        assert_eq!(pager_command(Some("more")), Some("more"));
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("cat")), None);
+    }
+
+    #[test]
+    fn test_exceeds_screen() {
+        assert!(!exceeds_screen("a\nb\n", 24, 80));
+        assert!(exceeds_screen(&"row\n".repeat(24), 24, 80));
+        assert!(exceeds_screen(&"x".repeat(81), 24, 80));
+        // Color codes don't count towards the width
+        assert!(!exceeds_screen("\u{1b}[1mheader\u{1b}[0m", 24, 6));
+    }
+}
@@ -0,0 +1,192 @@
+//! Client-side substitution of `--param key=value` pairs into query text.
+//!
+//! Placeholders are written as `:key` or `${key}`. Values are inserted verbatim
+//! apart from single quotes, which are doubled so a value placed inside a SQL
+//! string literal (`dt = ':date'`) can't end the literal early.
+
+use std::collections::HashMap;
+
+/// Parses a `key=value` command line argument
+pub fn parse_param(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", arg))?;
+
+    if key.is_empty() || !key.chars().all(is_name_char) {
+        return Err(format!(
+            "parameter names may only contain letters, digits and '_', got '{}'",
+            key
+        ));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Replaces `:key` and `${key}` placeholders in `query` with their values.
+///
+/// Unknown placeholders are left untouched, unless `strict` is set, in which
+/// case they are reported as an error.
+pub fn substitute_params(
+    query: &str,
+    params: &[(String, String)],
+    strict: bool,
+) -> Result<String, String> {
+    let values: HashMap<&str, &str> = params
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+
+    let chars: Vec<char> = query.chars().collect();
+    let mut result = String::with_capacity(query.len());
+    let mut unresolved: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let placeholder = match chars[i] {
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                braced_name(&chars, i + 2).map(|(name, end)| (name, end + 1))
+            }
+            // Skip "a:b" so times like '12:30:00' and map keys aren't treated as placeholders
+            ':' if i == 0 || !(is_name_char(chars[i - 1]) || chars[i - 1] == ':') => {
+                bare_name(&chars, i + 1)
+            }
+            _ => None,
+        };
+
+        match placeholder {
+            Some((name, end)) => {
+                match values.get(name.as_str()) {
+                    Some(value) => result.push_str(&value.replace('\'', "''")),
+                    None => {
+                        if !unresolved.contains(&name) {
+                            unresolved.push(name);
+                        }
+                        result.extend(&chars[i..end]);
+                    }
+                }
+                i = end;
+            }
+            None => {
+                result.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+
+    if strict && !unresolved.is_empty() {
+        return Err(format!(
+            "No value given for query parameter(s): {}",
+            unresolved.join(", ")
+        ));
+    }
+
+    Ok(result)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Name of a `:key` placeholder starting at `start`, and the index just past it
+fn bare_name(chars: &[char], start: usize) -> Option<(String, usize)> {
+    // Names start with a letter or '_' so ':30' in a time literal is left alone
+    if !chars
+        .get(start)
+        .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_')
+    {
+        return None;
+    }
+
+    let end = (start..chars.len())
+        .find(|&j| !is_name_char(chars[j]))
+        .unwrap_or(chars.len());
+    Some((chars[start..end].iter().collect(), end))
+}
+
+/// Name inside `${...}` starting at `start`, and the index of the closing brace
+fn braced_name(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let end = (start..chars.len()).find(|&j| chars[j] == '}')?;
+    let name: String = chars[start..end].iter().collect();
+    (!name.is_empty() && name.chars().all(is_name_char)).then_some((name, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_param() {
+        assert_eq!(
+            parse_param("date=2024-01-01"),
+            Ok(("date".to_string(), "2024-01-01".to_string()))
+        );
+        // Only the first '=' separates key and value
+        assert_eq!(
+            parse_param("filter=a=b"),
+            Ok(("filter".to_string(), "a=b".to_string()))
+        );
+        assert_eq!(
+            parse_param("region="),
+            Ok(("region".to_string(), String::new()))
+        );
+
+        assert!(parse_param("date").is_err());
+        assert!(parse_param("=value").is_err());
+        assert!(parse_param("my-date=2024").is_err());
+    }
+
+    #[test]
+    fn test_substitute_params() {
+        let values = params(&[("date", "2024-01-01"), ("region", "us"), ("dates", "x")]);
+
+        assert_eq!(
+            substitute_params(
+                "SELECT * FROM t WHERE dt = ':date' AND region = '${region}'",
+                &values,
+                true
+            ),
+            Ok("SELECT * FROM t WHERE dt = '2024-01-01' AND region = 'us'".to_string())
+        );
+
+        // Whole names only, and times or "a:b" text are not placeholders
+        assert_eq!(
+            substitute_params(
+                "SELECT ':dates', TIME '12:30:00', m['a:date'], ':date_end'",
+                &values,
+                false
+            ),
+            Ok("SELECT 'x', TIME '12:30:00', m['a:date'], ':date_end'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_substitute_params_escapes_quotes() {
+        let values = params(&[("name", "x' OR '1'='1")]);
+        assert_eq!(
+            substitute_params("WHERE name = ':name'", &values, true),
+            Ok("WHERE name = 'x'' OR ''1''=''1'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_substitute_params_strict() {
+        let values = params(&[("date", "2024-01-01")]);
+        let query = "WHERE dt = ':date' AND region = ':region' AND id = ${id}";
+
+        assert_eq!(
+            substitute_params(query, &values, false),
+            Ok("WHERE dt = '2024-01-01' AND region = ':region' AND id = ${id}".to_string())
+        );
+        assert_eq!(
+            substitute_params(query, &values, true),
+            Err("No value given for query parameter(s): region, id".to_string())
+        );
+    }
+}
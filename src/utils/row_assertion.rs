@@ -0,0 +1,131 @@
+//! `--assert-rows` expectations on the number of rows a query returns, such as
+//! `>100` or `==0`, for data-quality checks in scripts and pipelines.
+
+use std::fmt;
+
+/// Comparison operators, longest first so `<=` isn't read as `<`
+const OPERATORS: [(&str, Comparison); 5] = [
+    ("<=", Comparison::AtMost),
+    (">=", Comparison::AtLeast),
+    ("==", Comparison::Equal),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Less,
+    AtMost,
+    Equal,
+    AtLeast,
+    Greater,
+}
+
+/// An expected row count, e.g. `>100`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowAssertion {
+    pub comparison: Comparison,
+    pub count: u64,
+}
+
+impl RowAssertion {
+    /// Whether a query that returned `rows` rows meets the expectation
+    pub fn holds(&self, rows: u64) -> bool {
+        match self.comparison {
+            Comparison::Less => rows < self.count,
+            Comparison::AtMost => rows <= self.count,
+            Comparison::Equal => rows == self.count,
+            Comparison::AtLeast => rows >= self.count,
+            Comparison::Greater => rows > self.count,
+        }
+    }
+}
+
+impl fmt::Display for RowAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (operator, _) = OPERATORS
+            .iter()
+            .find(|(_, comparison)| *comparison == self.comparison)
+            .expect("every comparison has an operator");
+        write!(f, "{}{}", operator, self.count)
+    }
+}
+
+/// Parses an assertion such as `>100`, `<= 5` or `==0` from the command line
+pub fn parse_row_assertion(arg: &str) -> Result<RowAssertion, String> {
+    let expected = || {
+        format!(
+            "expected a comparison and a row count such as '>100' or '==0' \
+             (one of <, <=, ==, >=, >), got '{}'",
+            arg
+        )
+    };
+
+    let (comparison, count) = OPERATORS
+        .iter()
+        .find_map(|(operator, comparison)| {
+            arg.trim()
+                .strip_prefix(*operator)
+                .map(|count| (*comparison, count))
+        })
+        .ok_or_else(expected)?;
+    let count = count.trim().parse().map_err(|_| expected())?;
+
+    Ok(RowAssertion { comparison, count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_each_comparison() {
+        let parsed = |arg| parse_row_assertion(arg).unwrap();
+
+        assert_eq!(parsed("<10").comparison, Comparison::Less);
+        assert_eq!(parsed("<=10").comparison, Comparison::AtMost);
+        assert_eq!(parsed("==0").comparison, Comparison::Equal);
+        assert_eq!(parsed(">=10").comparison, Comparison::AtLeast);
+        assert_eq!(
+            parsed(" > 100 "),
+            RowAssertion {
+                comparison: Comparison::Greater,
+                count: 100
+            }
+        );
+    }
+
+    #[test]
+    fn test_each_comparison_holds() {
+        let holds = |arg, rows| parse_row_assertion(arg).unwrap().holds(rows);
+
+        assert!(holds("<10", 9));
+        assert!(!holds("<10", 10));
+        assert!(holds("<=10", 10));
+        assert!(!holds("<=10", 11));
+        assert!(holds("==0", 0));
+        assert!(!holds("==0", 1));
+        assert!(holds(">=10", 10));
+        assert!(!holds(">=10", 9));
+        assert!(holds(">100", 101));
+        assert!(!holds(">100", 100));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for arg in ["100", "=5", "!=5", ">", ">-1", ">ten", "=>5", ""] {
+            assert!(
+                parse_row_assertion(arg).is_err(),
+                "{} should be rejected",
+                arg
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        for arg in ["<1", "<=2", "==0", ">=3", ">100"] {
+            assert_eq!(parse_row_assertion(arg).unwrap().to_string(), arg);
+        }
+    }
+}
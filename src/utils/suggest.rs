@@ -0,0 +1,49 @@
+//! "Did you mean" suggestions for mistyped names.
+
+/// Maximum number of suggestions returned by [`closest_matches`]
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the candidates closest to `name` by edit distance (ignoring case),
+/// best match first. Candidates needing more than a third of the name's length
+/// in edits (at least 2) aren't considered close enough to suggest.
+pub fn closest_matches<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                strsim::levenshtein(&name, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_matches() {
+        let tables = ["orders", "order_items", "customers", "ordres_archive"];
+
+        assert_eq!(closest_matches("ordrs", tables), vec!["orders"]);
+        assert_eq!(closest_matches("ORDER_ITEM", tables), vec!["order_items"]);
+        assert_eq!(closest_matches("customer", tables), vec!["customers"]);
+        assert!(closest_matches("invoices", tables).is_empty());
+    }
+}
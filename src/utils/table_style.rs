@@ -0,0 +1,113 @@
+//! The border style shared by every table the CLI prints, chosen with
+//! `app.table_style` or `--table-style`.
+
+use crate::config::TableStyle;
+use prettytable::format::{self, FormatBuilder, LinePosition, LineSeparator, TableFormat};
+use prettytable::{Cell, Row, Table};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static STYLE: AtomicU8 = AtomicU8::new(TableStyle::Ascii as u8);
+
+/// Apply the table style globally; must run before any command renders output
+pub fn configure(style: TableStyle) {
+    STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+fn current() -> TableStyle {
+    match STYLE.load(Ordering::Relaxed) {
+        s if s == TableStyle::Clean as u8 => TableStyle::Clean,
+        s if s == TableStyle::Markdown as u8 => TableStyle::Markdown,
+        s if s == TableStyle::Borderless as u8 => TableStyle::Borderless,
+        _ => TableStyle::Ascii,
+    }
+}
+
+/// Prints a table to stdout in the configured style
+pub fn print(table: &Table) {
+    styled(table).printstd();
+}
+
+/// A copy of `table` in the configured style
+pub fn styled(table: &Table) -> Table {
+    with_style(table, current())
+}
+
+fn with_style(table: &Table, style: TableStyle) -> Table {
+    let mut table = match style {
+        TableStyle::Markdown => markdown(table),
+        _ => table.clone(),
+    };
+    table.set_format(table_format(style));
+    table
+}
+
+fn table_format(style: TableStyle) -> TableFormat {
+    match style {
+        TableStyle::Ascii => *format::consts::FORMAT_DEFAULT,
+        TableStyle::Clean => *format::consts::FORMAT_CLEAN,
+        TableStyle::Borderless => FormatBuilder::new()
+            .column_separator('|')
+            .padding(1, 1)
+            .build(),
+        TableStyle::Markdown => FormatBuilder::new()
+            .column_separator('|')
+            .borders('|')
+            .separator(LinePosition::Title, LineSeparator::new('-', '|', '|', '|'))
+            .padding(1, 1)
+            .build(),
+    }
+}
+
+/// Rebuilds a table as GitHub-flavored markdown: the first row becomes the
+/// header, styles are dropped and `|` in values is escaped
+fn markdown(table: &Table) -> Table {
+    let plain_row = |row: &Row| {
+        Row::new(
+            row.iter()
+                .map(|cell| Cell::new(&cell.get_content().replace('|', "\\|")))
+                .collect(),
+        )
+    };
+
+    let mut rows = table.row_iter();
+    let mut markdown = Table::new();
+    if let Some(header) = rows.next() {
+        markdown.set_titles(plain_row(header));
+    }
+    for row in rows {
+        markdown.add_row(plain_row(row));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![
+            Cell::new("Name").style_spec("Fb"),
+            Cell::new("Value").style_spec("Fb"),
+        ]));
+        table.add_row(Row::new(vec![Cell::new("a|b"), Cell::new("1")]));
+        table
+    }
+
+    #[test]
+    fn test_markdown_style() {
+        let rendered = with_style(&sample(), TableStyle::Markdown).to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["| Name | Value |", "|------|-------|", "| a\\|b | 1     |"]
+        );
+    }
+
+    #[test]
+    fn test_clean_style_has_no_borders() {
+        let rendered = with_style(&sample(), TableStyle::Clean).to_string();
+        assert!(!rendered.contains('+'));
+        assert!(!rendered.contains('-'));
+    }
+}
@@ -8,12 +8,21 @@
 //!
 //! - SQL syntax validation using ANSI SQL standards
 //! - Detailed error messages for syntax issues
+//! - Rejection of queries longer than Athena accepts
+//! - Rejection of multi-statement scripts, since Athena runs one statement per execution
 //! - Validation before query execution to save time and costs
+//! - Extraction of the tables a query reads, for checking they exist
+//! - Adding a row limit to queries that don't have one
+//! - Wrapping a query to count its rows
+//! - Splitting a script into its statements, for running them one by one
+//! - Finding the table a CTAS creates and the location an UNLOAD writes to
 
+use crate::error::AthenaCliError;
 use anyhow::{Context, Result};
-use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::ast::{visit_relations, Expr, Query, SetExpr, Statement, Value};
 use sqlparser::dialect::AnsiDialect;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Location, Token, Tokenizer};
 
 /// Validates the syntax of an Athena SQL query.
 ///
@@ -29,6 +38,7 @@ use sqlparser::parser::Parser;
 ///
 /// * `Ok(())` if the query syntax is valid
 /// * `Err(anyhow::Error)` with a descriptive error message if the syntax is invalid
+///   or the query contains more than one statement
 ///
 /// # Examples
 ///
@@ -50,6 +60,17 @@ pub fn validate_query_syntax(query: &str) -> Result<()> {
     // Attempt to parse the SQL query
     match Parser::parse_sql(&dialect, query) {
         Ok(statements) => {
+            // Athena executes exactly one statement per call. Empty statements and
+            // trailing semicolons are already dropped by the parser.
+            if statements.len() > 1 {
+                return Err(AthenaCliError::Validation(format!(
+                    "Query contains {} statements, but Athena runs one statement at a time. \
+                     Please split the script and run each statement separately.",
+                    statements.len()
+                ))
+                .into());
+            }
+
             // Additional validation for SELECT statements
             for stmt in statements {
                 if let Statement::Query(query_box) = stmt {
@@ -59,13 +80,132 @@ pub fn validate_query_syntax(query: &str) -> Result<()> {
             Ok(())
         }
         Err(e) => {
-            // Return a user-friendly error message
-            Err(anyhow::anyhow!("SQL syntax error: {}", e))
-                .with_context(|| format!("Failed to parse query: {}", query))
+            // Point at the offending token when it can be found, otherwise show
+            // the whole query
+            let message = e.to_string();
+            match error_location(query, &message) {
+                Some((location, width)) => Err(AthenaCliError::Validation(format!(
+                    "SQL syntax error at line {}, column {}: {}\n{}",
+                    location.line,
+                    location.column,
+                    message,
+                    caret_snippet(query, &location, width)
+                ))
+                .into()),
+                None => Err(AthenaCliError::Validation(format!(
+                    "SQL syntax error: {}",
+                    message
+                )))
+                .with_context(|| format!("Failed to parse query: {}", query)),
+            }
+        }
+    }
+}
+
+/// Longest query string Athena accepts, in bytes
+pub const MAX_QUERY_BYTES: usize = 262_144;
+
+/// Checks that a query fits within Athena's query string limit.
+///
+/// The limit is in bytes of UTF-8, so multibyte characters count for more than
+/// one. Athena only reports an opaque validation error for longer queries, so
+/// this gives a clear error before the query is sent.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::{validate_query_length, MAX_QUERY_BYTES};
+///
+/// assert!(validate_query_length("SELECT 1").is_ok());
+/// assert!(validate_query_length(&"x".repeat(MAX_QUERY_BYTES + 1)).is_err());
+/// ```
+pub fn validate_query_length(query: &str) -> Result<()> {
+    if query.len() <= MAX_QUERY_BYTES {
+        return Ok(());
+    }
+
+    Err(AthenaCliError::Validation(format!(
+        "Query is {} bytes, over Athena's limit of {} bytes. \
+         Move large inline value lists into a table (e.g. with CREATE TABLE AS or an \
+         uploaded CSV) and join against it, or split the logic into views.",
+        query.len(),
+        MAX_QUERY_BYTES
+    ))
+    .into())
+}
+
+/// Finds where a parse error occurred, with the width of the offending token.
+///
+/// Tokenizer errors carry their location. Parser errors only name the token
+/// they found, so the query is parsed again and the token is looked up next to
+/// where the parser stopped: the token it just consumed or the one after it.
+fn error_location(query: &str, message: &str) -> Option<(Location, usize)> {
+    let dialect = AnsiDialect {};
+    let tokens = match Tokenizer::new(&dialect, query).tokenize_with_location() {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let location = Location {
+                line: e.line,
+                column: e.col,
+            };
+            return Some((location, 1));
         }
+    };
+
+    let mut parser = Parser::new(&dialect).with_tokens_with_locations(tokens);
+    parser.parse_statements().err()?;
+
+    let found = message.split("found: ").nth(1)?;
+    let found = found.split(" at Line: ").next().unwrap_or(found).trim();
+    if found == Token::EOF.to_string() {
+        return Some((end_location(query), 1));
+    }
+
+    let next = parser.peek_token();
+    parser.prev_token();
+    let consumed = parser.peek_token();
+    let token = [consumed, next]
+        .into_iter()
+        .find(|token| token.token.to_string() == found && token.location.line > 0)?;
+
+    Some((token.location, found.chars().count().max(1)))
+}
+
+/// Location just after the last character of `text`
+fn end_location(text: &str) -> Location {
+    let line = text.split('\n').count().max(1);
+    let last_line = text.rsplit('\n').next().unwrap_or_default();
+    Location {
+        line: line as u64,
+        column: last_line.chars().count() as u64 + 1,
     }
 }
 
+/// The line of `query` at `location`, with `^` marks under the `width`
+/// characters starting there
+fn caret_snippet(query: &str, location: &Location, width: usize) -> String {
+    let line = query
+        .lines()
+        .nth(location.line.saturating_sub(1) as usize)
+        .unwrap_or_default();
+    let number = location.line.to_string();
+    // Keep tabs so the marks line up with the text above them
+    let indent: String = line
+        .chars()
+        .take(location.column.saturating_sub(1) as usize)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    format!(
+        "{} | {}\n{} | {}{}",
+        number,
+        line,
+        " ".repeat(number.len()),
+        indent,
+        "^".repeat(width)
+    )
+}
+
 /// Validates a SELECT query for common issues that might not be caught by the parser.
 fn validate_select_query(query: Query) -> Result<()> {
     // Check if it's a simple SELECT query
@@ -85,9 +225,10 @@ fn validate_select_query(query: Query) -> Result<()> {
             });
 
             if !is_special_case {
-                return Err(anyhow::anyhow!(
-                    "SQL syntax error: SELECT query missing FROM clause"
-                ));
+                return Err(AthenaCliError::Validation(
+                    "SQL syntax error: SELECT query missing FROM clause".to_string(),
+                )
+                .into());
             }
         }
     }
@@ -95,6 +236,258 @@ fn validate_select_query(query: Query) -> Result<()> {
     Ok(())
 }
 
+/// Returns the tables a SELECT query reads from, in order of first appearance.
+///
+/// Each table is returned as its dot-separated name parts as written, e.g.
+/// `["sales", "orders"]` for `sales.orders`. Names of common table expressions
+/// defined in `WITH` clauses are left out, since they aren't real tables. Other
+/// statement types (DDL, INSERT, ...) return no tables.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::referenced_tables;
+///
+/// let tables = referenced_tables("SELECT * FROM sales.orders o JOIN customers c ON o.cid = c.id")
+///     .unwrap();
+/// assert_eq!(tables, vec![vec!["sales", "orders"], vec!["customers"]]);
+/// ```
+pub fn referenced_tables(query: &str) -> Result<Vec<Vec<String>>> {
+    let statements = Parser::parse_sql(&AnsiDialect {}, query)
+        .map_err(|e| anyhow::anyhow!("SQL syntax error: {}", e))?;
+
+    let mut tables: Vec<Vec<String>> = Vec::new();
+    for statement in &statements {
+        let Statement::Query(query) = statement else {
+            continue;
+        };
+
+        let mut cte_names = Vec::new();
+        collect_cte_names(query, &mut cte_names);
+
+        let _ = visit_relations(statement, |relation| {
+            let parts: Vec<String> = relation.0.iter().map(|ident| ident.value.clone()).collect();
+            let is_cte = parts.len() == 1
+                && cte_names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&parts[0]));
+            if !is_cte && !tables.contains(&parts) {
+                tables.push(parts);
+            }
+            std::ops::ControlFlow::<()>::Continue(())
+        });
+    }
+
+    Ok(tables)
+}
+
+/// Collects the names defined by a query's `WITH` clause, including those of
+/// nested `WITH` clauses inside the common table expressions themselves
+fn collect_cte_names(query: &Query, names: &mut Vec<String>) {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            names.push(cte.alias.name.value.clone());
+            collect_cte_names(&cte.query, names);
+        }
+    }
+}
+
+/// Adds `LIMIT limit` to a query that has no `LIMIT` or `FETCH` clause.
+///
+/// The limit is set on the parsed AST and the statement is rendered back to
+/// SQL, so it lands in the right place even after `ORDER BY` or a `UNION`.
+/// Returns `None` when the query is left unchanged: it isn't a single query
+/// statement, or it already limits its rows.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::apply_row_limit;
+///
+/// assert_eq!(
+///     apply_row_limit("SELECT * FROM my_table", 100).unwrap().as_deref(),
+///     Some("SELECT * FROM my_table LIMIT 100")
+/// );
+/// assert_eq!(apply_row_limit("SELECT * FROM my_table LIMIT 5", 100).unwrap(), None);
+/// ```
+pub fn apply_row_limit(query: &str, limit: u64) -> Result<Option<String>> {
+    let mut statements = Parser::parse_sql(&AnsiDialect {}, query)
+        .map_err(|e| anyhow::anyhow!("SQL syntax error: {}", e))?;
+
+    let [Statement::Query(query)] = statements.as_mut_slice() else {
+        return Ok(None);
+    };
+    if query.limit.is_some() || query.fetch.is_some() {
+        return Ok(None);
+    }
+
+    query.limit = Some(Expr::Value(Value::Number(limit.to_string(), false)));
+    Ok(Some(query.to_string()))
+}
+
+/// Wraps a query so that it returns only the number of rows it would return.
+///
+/// Only a single query statement (SELECT, WITH, VALUES...) can be counted;
+/// anything else is a validation error.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::count_rows_query;
+///
+/// assert_eq!(
+///     count_rows_query("SELECT id FROM my_table WHERE id > 1").unwrap(),
+///     "SELECT count(*) FROM (SELECT id FROM my_table WHERE id > 1)"
+/// );
+/// assert!(count_rows_query("DROP TABLE my_table").is_err());
+/// ```
+pub fn count_rows_query(query: &str) -> Result<String> {
+    let statements = Parser::parse_sql(&AnsiDialect {}, query)
+        .map_err(|e| anyhow::anyhow!("SQL syntax error: {}", e))?;
+
+    match statements.as_slice() {
+        // The parsed query is used rather than the text, so a trailing
+        // comment can't swallow the closing parenthesis
+        [Statement::Query(query)] => Ok(format!("SELECT count(*) FROM ({})", query)),
+        _ => Err(AthenaCliError::Validation(
+            "--count-only needs a single SELECT query".to_string(),
+        )
+        .into()),
+    }
+}
+
+/// Splits a script into its `;`-separated statements.
+///
+/// The script is tokenized rather than split on every `;`, so semicolons
+/// inside string literals, quoted identifiers and comments don't end a
+/// statement. Each statement keeps its original text, trimmed; empty ones and
+/// those holding only comments are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::split_statements;
+///
+/// let statements = split_statements("SELECT 'a;b';\nSELECT 2;").unwrap();
+/// assert_eq!(statements, vec!["SELECT 'a;b'", "SELECT 2"]);
+/// ```
+pub fn split_statements(script: &str) -> Result<Vec<String>> {
+    let tokens = Tokenizer::new(&AnsiDialect {}, script)
+        .tokenize_with_location()
+        .map_err(|e| AthenaCliError::Validation(format!("SQL syntax error: {}", e)))?;
+
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut has_content = false;
+    for token in tokens {
+        match token.token {
+            Token::SemiColon => {
+                let end = byte_offset(script, &token.location);
+                if has_content {
+                    statements.push(script[start..end].trim().to_string());
+                }
+                start = end + 1;
+                has_content = false;
+            }
+            Token::Whitespace(_) | Token::EOF => {}
+            _ => has_content = true,
+        }
+    }
+    if has_content {
+        statements.push(script[start..].trim().to_string());
+    }
+
+    Ok(statements)
+}
+
+/// The table a `CREATE TABLE ... AS SELECT` statement creates, or `None` for
+/// any other statement
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::created_table;
+///
+/// assert_eq!(
+///     created_table("CREATE TABLE sales.daily AS SELECT * FROM orders").as_deref(),
+///     Some("sales.daily")
+/// );
+/// assert_eq!(created_table("SELECT 1"), None);
+/// ```
+pub fn created_table(query: &str) -> Option<String> {
+    let statements = Parser::parse_sql(&AnsiDialect {}, query).ok()?;
+    match statements.as_slice() {
+        [Statement::CreateTable {
+            name,
+            query: Some(_),
+            ..
+        }] => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// The S3 location an `UNLOAD (...) TO 's3://...'` statement writes to, or
+/// `None` for any other statement. The parser doesn't know UNLOAD, so the
+/// location is found from the tokens: the string after the first `TO` outside
+/// the parentheses.
+///
+/// # Examples
+///
+/// ```
+/// use athena_cli::validation::unload_location;
+///
+/// assert_eq!(
+///     unload_location("UNLOAD (SELECT * FROM t) TO 's3://bucket/out/' WITH (format = 'PARQUET')")
+///         .as_deref(),
+///     Some("s3://bucket/out/")
+/// );
+/// assert_eq!(unload_location("SELECT 1"), None);
+/// ```
+pub fn unload_location(query: &str) -> Option<String> {
+    let tokens = Tokenizer::new(&AnsiDialect {}, query).tokenize().ok()?;
+    let mut tokens = tokens
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)));
+
+    match tokens.next()? {
+        Token::Word(word) if word.value.eq_ignore_ascii_case("UNLOAD") => {}
+        _ => return None,
+    }
+
+    let mut depth = 0i32;
+    let mut after_to = false;
+    for token in tokens {
+        if after_to {
+            return match token {
+                Token::SingleQuotedString(location) => Some(location),
+                _ => None,
+            };
+        }
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            Token::Word(word) if depth == 0 && word.value.eq_ignore_ascii_case("TO") => {
+                after_to = true
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Byte offset in `text` of a tokenizer location (1-based line and character column)
+fn byte_offset(text: &str, location: &Location) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(location.line.saturating_sub(1) as usize)
+        .map(str::len)
+        .sum();
+    text[line_start..]
+        .char_indices()
+        .nth(location.column.saturating_sub(1) as usize)
+        .map_or(text.len(), |(offset, _)| line_start + offset)
+}
+
 /// Checks if the query is a DDL (Data Definition Language) statement.
 ///
 /// DDL statements include CREATE, ALTER, DROP, etc. This function is useful
@@ -167,6 +560,48 @@ fn validate_select_query(query: Query) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_query_length_limit() {
+        // A long IN list, as generated by scripts
+        let values = (0..40_000)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!("SELECT * FROM orders WHERE id IN ({})", values);
+        assert!(query.len() > MAX_QUERY_BYTES);
+
+        let err = validate_query_length(&query).unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "Query is {} bytes, over Athena's limit of 262144 bytes",
+            query.len()
+        )));
+
+        // The limit counts bytes: fewer characters than the limit can still exceed it
+        let multibyte = format!("SELECT '{}'", "é".repeat(MAX_QUERY_BYTES / 2));
+        assert!(multibyte.chars().count() < MAX_QUERY_BYTES);
+        assert!(validate_query_length(&multibyte).is_err());
+
+        let at_limit = format!("SELECT '{}'", "x".repeat(MAX_QUERY_BYTES - 9));
+        assert_eq!(at_limit.len(), MAX_QUERY_BYTES);
+        assert!(validate_query_length(&at_limit).is_ok());
+    }
+
+    #[test]
+    fn test_count_rows_query() {
+        assert_eq!(
+            count_rows_query("SELECT * FROM orders LIMIT 10 -- sample").unwrap(),
+            "SELECT count(*) FROM (SELECT * FROM orders LIMIT 10)"
+        );
+        assert_eq!(
+            count_rows_query("WITH t AS (SELECT 1 AS x) SELECT x FROM t").unwrap(),
+            "SELECT count(*) FROM (WITH t AS (SELECT 1 AS x) SELECT x FROM t)"
+        );
+
+        let err = count_rows_query("INSERT INTO orders VALUES (1)").unwrap_err();
+        assert!(err.to_string().contains("needs a single SELECT query"));
+        assert!(count_rows_query("SELECT 1; SELECT 2").is_err());
+    }
+
     #[test]
     fn test_valid_syntax() {
         let valid_queries = vec![
@@ -203,14 +638,148 @@ mod tests {
         ];
 
         for query in invalid_queries {
+            let err = validate_query_syntax(query).unwrap_err();
             assert!(
-                validate_query_syntax(query).is_err(),
+                matches!(
+                    AthenaCliError::find(&err),
+                    Some(AthenaCliError::Validation(_))
+                ),
                 "Query should be invalid: {}",
                 query
             );
         }
     }
 
+    #[test]
+    fn test_created_table() {
+        assert_eq!(
+            created_table(
+                "CREATE TABLE reports.daily WITH (format = 'PARQUET') AS SELECT * FROM orders"
+            )
+            .as_deref(),
+            Some("reports.daily")
+        );
+        assert_eq!(created_table("CREATE TABLE t (id INT)"), None);
+        assert_eq!(created_table("SELECT * FROM orders"), None);
+    }
+
+    #[test]
+    fn test_unload_location() {
+        let query = "-- tag:team=analytics\nUNLOAD (\n  SELECT 'to' AS x FROM t WHERE a IN (SELECT b FROM u)\n) \
+                     TO 's3://bucket/exports/' WITH (format = 'PARQUET')";
+        assert_eq!(
+            unload_location(query).as_deref(),
+            Some("s3://bucket/exports/")
+        );
+        assert_eq!(unload_location("SELECT * FROM t"), None);
+        assert_eq!(unload_location("UNLOAD (SELECT 1)"), None);
+    }
+
+    #[test]
+    fn test_syntax_error_position() {
+        let err = validate_query_syntax("SELECT *\nFORM my_table").unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("at line 2, column 1"),
+            "unexpected message: {}",
+            message
+        );
+        assert!(message.ends_with("2 | FORM my_table\n  | ^^^^"));
+
+        let err = validate_query_syntax("SELECT * FROM t WHERE id = 'abc").unwrap_err();
+        assert!(err.to_string().contains("at line 1, column 28"), "{}", err);
+    }
+
+    #[test]
+    fn test_caret_snippet() {
+        let location = Location { line: 2, column: 5 };
+        assert_eq!(
+            caret_snippet("SELECT\n\tid FORM t", &location, 4),
+            "2 | \tid FORM t\n  | \t   ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_multiple_statements_rejected() {
+        let err = validate_query_syntax("SELECT 1; SELECT 2").unwrap_err();
+        assert!(err.to_string().contains("2 statements"));
+    }
+
+    #[test]
+    fn test_trailing_semicolon_allowed() {
+        assert!(validate_query_syntax("SELECT 1;").is_ok());
+        assert!(validate_query_syntax("SELECT 1;;").is_ok());
+        assert!(validate_query_syntax(";SELECT 1;").is_ok());
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let script = "-- daily reports\n\
+                      SELECT 'a;b' AS \"x;y\" FROM t; /* ; */\n\
+                      SELECT 'café', 2 FROM t WHERE s = 'it''s;'\n;;\n\
+                      -- trailing comment;\n";
+        assert_eq!(
+            split_statements(script).unwrap(),
+            vec![
+                "-- daily reports\nSELECT 'a;b' AS \"x;y\" FROM t",
+                "/* ; */\nSELECT 'café', 2 FROM t WHERE s = 'it''s;'",
+            ]
+        );
+        assert!(split_statements("  ; -- nothing\n").unwrap().is_empty());
+        assert!(split_statements("SELECT 'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_referenced_tables() {
+        let tables = referenced_tables(
+            "WITH recent AS (SELECT * FROM sales.orders WHERE dt > '2024-01-01') \
+             SELECT * FROM recent r \
+             JOIN customers c ON r.customer_id = c.id \
+             WHERE c.id IN (SELECT customer_id FROM awsdatacatalog.sales.vip) \
+             UNION ALL SELECT * FROM sales.orders",
+        )
+        .unwrap();
+
+        assert_eq!(
+            tables,
+            vec![
+                vec!["sales", "orders"],
+                vec!["customers"],
+                vec!["awsdatacatalog", "sales", "vip"],
+            ]
+        );
+
+        assert!(referenced_tables("SELECT 1").unwrap().is_empty());
+        assert!(referenced_tables("DROP TABLE my_table").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_row_limit() {
+        assert_eq!(
+            apply_row_limit("SELECT id FROM orders ORDER BY id DESC;", 1000)
+                .unwrap()
+                .as_deref(),
+            Some("SELECT id FROM orders ORDER BY id DESC LIMIT 1000")
+        );
+        assert_eq!(
+            apply_row_limit("SELECT id FROM a UNION ALL SELECT id FROM b", 10)
+                .unwrap()
+                .as_deref(),
+            Some("SELECT id FROM a UNION ALL SELECT id FROM b LIMIT 10")
+        );
+
+        // Queries that already limit their rows, and other statements, are left alone
+        assert_eq!(
+            apply_row_limit("SELECT * FROM t LIMIT 5", 10).unwrap(),
+            None
+        );
+        assert_eq!(
+            apply_row_limit("SELECT * FROM t FETCH FIRST 5 ROWS ONLY", 10).unwrap(),
+            None
+        );
+        assert_eq!(apply_row_limit("DROP TABLE t", 10).unwrap(), None);
+    }
+
     //#[test]
     //fn test_ddl_detection() {
     //assert!(is_ddl_statement("CREATE TABLE my_table (id INT)"));